@@ -14,16 +14,50 @@ pub enum Command {
     Ping,
     #[command(description = "initialize users from chat")]
     InitUsers,
-    #[command(description = "add new dictionary entry: \n/add tg_username trigger=reply or \n/add all trigger=reply")]
+    #[command(description = "add new dictionary entry: \n/add @username trigger=reply, \n/add trigger=reply in reply to the user's message or \n/add all trigger=reply\noptional: @30% probability, ttl=7d expiry, days=mon-fri hours=18-23 active window, on=text,caption,poll,forward content types\nreply to a sticker with /add all =reply (or emoji=reply, set=reply) for sticker triggers\n/add @username mention=reply (or mention:word=reply) fires when someone mentions or replies to them\nput one entry per line to add several at once")]
     Add,
-    #[command(description = "delete dictionary entry: \n/delete tg_username trigger or \n/delete all trigger", hide)]
+    #[command(description = "delete dictionary entry: \n/delete @username trigger or \n/delete all trigger", hide)]
     Delete,
-    #[command(description = "change trigger reply frequency: /changefrq 4")]
+    #[command(description = "change a trigger's reply, keeping its options: \n/edit @username trigger=new reply or \n/edit all trigger=new reply")]
+    Edit,
+    #[command(description = "rename a trigger: \n/rename @username old=>new or \n/rename all old=>new")]
+    Rename,
+    #[command(description = "copy triggers from another chat, admins of both only: \n/copytriggers from_chat_id [to_chat_id] [skip|overwrite|rename] [trigger1, trigger2]")]
+    CopyTriggers,
+    #[command(description = "list available trigger packs: /packs")]
+    Packs,
+    #[command(description = "install a trigger pack: /installpack name")]
+    InstallPack,
+    #[command(description = "uninstall a trigger pack: /uninstallpack name")]
+    UninstallPack,
+    #[command(description = "change how often triggers reply: /changefrq 4 (every 4th match), /changefrq random 30%, /changefrq interval 10m, /changefrq burst 3 10m (3 at once, one more every 10m), /changefrq count all|matched (which messages advance the counter), or for one user: /changefrq @username 4 (off to reset)")]
     ChangeFrq,
     #[command(description = "set roast level [1-5]: /setroastlvl 4")]
     SetRoastLvl,
+    #[command(description = "how many recent messages roasts see [0-50]: /roastcontext 10")]
+    RoastContext,
+    #[command(description = "roast profile notes: /profile @username note, /profile @username to view, /profile to view yours, /profile clear to clear yours")]
+    Profile,
+    #[command(description = "set chat timezone as UTC offset: /settz +5")]
+    SetTz,
+    #[command(description = "fire triggers on edited messages: /watchedits on [caught you editing reply] or /watchedits off")]
+    WatchEdits,
+    #[command(description = "no trigger replies or roasts during these local hours: /quiethours 23-8 or /quiethours off")]
+    QuietHours,
+    #[command(description = "silence trigger replies and roasts for a while: /shutup 30m")]
+    ShutUp,
+    #[command(description = "cancel /shutup early: /wake")]
+    Wake,
+    #[command(description = "show this chat's settings and reply counter: /settings")]
+    Settings,
+    #[command(description = "browse triggers: /list, /list @username or /list all")]
+    List,
+    #[command(description = "show most and never fired triggers: /triggerstats")]
+    TriggerStats,
+    #[command(description = "show AI provider usage since startup: /llmstats", hide)]
+    LlmStats,
     #[command(description = "get dictionary entries: /getdict", hide)]
     GetDict,
-    #[command(description = "set dictionary entries: /setdict", hide)]
+    #[command(description = "set dictionary entries: /setdict, a file with only \"dialogs\" imports dialogs into this chat, one with only \"packs\" adds trigger packs", hide)]
     SetDict,
 }
\ No newline at end of file
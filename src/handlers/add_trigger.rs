@@ -1,6 +1,17 @@
 use teloxide::{prelude::*, types::MessageId};
-use crate::utils::dictionary::{add_user_trigger, add_common_trigger};
+use crate::utils::dictionary::{
+    add_user_trigger, add_common_trigger, add_user_sticker_trigger, add_common_sticker_trigger, add_user_mention_trigger,
+    add_triggers,
+    ActiveWindow, ContentKind, EntryTarget, TriggerReply,
+};
+use crate::handlers::target::{command_args, mention_keyword, parse_target};
+use crate::utils::sticker::sticker_key;
+use crate::utils::time::{parse_duration, parse_hours, parse_weekdays};
+use chrono::Utc;
 use std::time::Duration;
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /add @username trigger=reply (or reply to their message) [@30%] [ttl=7d] [days=mon-fri] [hours=18-23] [on=text,caption,poll,forward]";
 
 pub async fn add_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
     if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
@@ -8,51 +19,117 @@ pub async fn add_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
     }
 
     if let Some(text) = msg.text() {
-        let parts: Vec<&str> = text.splitn(3, ' ').collect();
-        if parts.len() < 3 {
-            bot.send_message(msg.chat.id, "Invalid format. Usage: /add tg_username trigger=reply").await?;
-            return Ok(());
+        let args = command_args(text);
+        if args.trim().contains('\n') {
+            return add_triggers_bulk(bot, &msg, args).await;
         }
 
-        let tg_username = parts[1].trim_start_matches('@').to_string();
-        let trigger = parts[2];
+        let Some((target, trigger)) = parse_target(&msg, args) else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+
         let trigger_details: Vec<&str> = trigger.splitn(2, '=').collect();
         if trigger_details.len() < 2 {
-            bot.send_message(msg.chat.id, "Invalid format. Usage: /add tg_username trigger=reply").await?;
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
             return Ok(());
         }
 
         let trigger_key = trigger_details[0].trim().to_lowercase();
-        let trigger_value = trigger_details[1].trim().to_string();
+        let trigger_value = match parse_trigger_reply(trigger_details[1]) {
+            Ok(reply) => reply,
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("Invalid format. {}", e)).limited().await?;
+                return Ok(());
+            }
+        };
 
         let chat_id = msg.chat.id.0.to_string();
-        
-        if tg_username == "all" {
-            match add_common_trigger(chat_id, trigger_key.clone(), trigger_value.clone()) {
+
+        // Replying to a sticker with "=reply", "emoji=reply" or "set=reply" keys the trigger on the sticker
+        let replied_sticker = msg.reply_to_message().and_then(|m| m.sticker());
+        if let Some(key) = replied_sticker.and_then(|sticker| sticker_key(sticker, &trigger_key)) {
+            let result = match &target {
+                None => add_common_sticker_trigger(chat_id, key.clone(), trigger_value),
+                Some(user) => add_user_sticker_trigger(chat_id, user, key.clone(), trigger_value),
+            };
+
+            match result {
+                Ok(_) => {
+                    let success_msg = bot.send_message(
+                        msg.chat.id,
+                        format!("Added sticker '{}' to your triggers dictionary!", key)
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
+                }
+                Err(e) => {
+                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).limited().await?;
+                }
+            }
+            return Ok(());
+        }
+
+        // "mention=reply" fires whenever someone mentions or replies to the user
+        if let Some(keyword) = mention_keyword(&trigger_key) {
+            let Some(user) = &target else {
+                bot.send_message(msg.chat.id, "Invalid format. Mention triggers need a user: /add @username mention=reply").limited().await?;
+                return Ok(());
+            };
+
+            match add_user_mention_trigger(chat_id, user, keyword.to_string(), trigger_value) {
                 Ok(_) => {
                     let success_msg = bot.send_message(
                         msg.chat.id,
                         format!("Added '{}' to your triggers dictionary!", trigger_key)
-                    ).await?;
-                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
+                }
+                Err(e) => {
+                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).limited().await?;
+                }
+            }
+            return Ok(());
+        }
+
+        if trigger_key.is_empty() {
+            bot.send_message(msg.chat.id, "Invalid format. Trigger must not be empty").limited().await?;
+            return Ok(());
+        }
+
+        if let Some(user) = &target {
+            match add_user_trigger(chat_id, user, trigger_key.clone(), trigger_value.clone()) {
+                Ok(_) => {
+                    let success_msg = bot.send_message(
+                        msg.chat.id,
+                        format!("Added '{}' to your triggers dictionary!", trigger_key)
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
 
                 }
                 Err(e) => {
-                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).await?;
+                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).limited().await?;
                 }
             }
         } else {
-            match add_user_trigger(chat_id, tg_username, trigger_key.clone(), trigger_value.clone()) {
+            match add_common_trigger(chat_id, trigger_key.clone(), trigger_value.clone()) {
                 Ok(_) => {
                     let success_msg = bot.send_message(
                         msg.chat.id,
                         format!("Added '{}' to your triggers dictionary!", trigger_key)
-                    ).await?;
-                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
 
                 }
                 Err(e) => {
-                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).await?;
+                    bot.send_message(msg.chat.id, format!("Error adding trigger: {}", e)).limited().await?;
                 }
             }
         }
@@ -61,6 +138,116 @@ pub async fn add_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
     Ok(())
 }
 
+// Adds one "@user trigger=reply" or "all trigger=reply" per line in a single save,
+// then reports accepted and rejected lines together
+async fn add_triggers_bulk(bot: Bot, msg: &Message, args: &str) -> ResponseResult<()> {
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+
+    for line in args.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match parse_trigger_line(msg, line) {
+            Ok(entry) => accepted.push(entry),
+            Err(e) => rejected.push(format!("{} — {}", line, e)),
+        }
+    }
+
+    let chat_id = msg.chat.id.0.to_string();
+    let accepted_count = accepted.len();
+    if !accepted.is_empty() {
+        if let Err(e) = add_triggers(chat_id, accepted) {
+            bot.send_message(msg.chat.id, format!("Error adding triggers: {}", e)).limited().await?;
+            return Ok(());
+        }
+    }
+
+    let mut summary = format!("Added {} triggers to your triggers dictionary!", accepted_count);
+    if !rejected.is_empty() {
+        summary.push_str(&format!("\nRejected {} lines:\n{}", rejected.len(), rejected.join("\n")));
+    }
+
+    let summary_msg = bot.send_message(msg.chat.id, summary).limited().await?;
+    if rejected.is_empty() {
+        if let Some(summary_msg) = summary_msg {
+            tokio::spawn(delete_message_after_delay(bot.clone(), summary_msg.chat.id, summary_msg.id, 1));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_trigger_line(msg: &Message, line: &str) -> Result<(EntryTarget, String, TriggerReply), String> {
+    let (target, trigger) = parse_target(msg, line).ok_or("expected @username or all")?;
+    let (trigger_key, reply) = trigger.split_once('=').ok_or("expected trigger=reply")?;
+
+    let trigger_key = trigger_key.trim().to_lowercase();
+    let reply = parse_trigger_reply(reply)?;
+
+    // "mention=reply" and "mention:word=reply" go to the user's mention triggers, as in single-line /add
+    if let Some(keyword) = mention_keyword(&trigger_key) {
+        let user = target.ok_or("mention triggers need @username")?;
+        return Ok((EntryTarget::Mention(user), keyword.to_string(), reply));
+    }
+
+    if trigger_key.is_empty() {
+        return Err("trigger must not be empty".to_string());
+    }
+
+    let target = match target {
+        Some(user) => EntryTarget::User(user),
+        None => EntryTarget::Common,
+    };
+    Ok((target, trigger_key, reply))
+}
+
+// Parses "reply @30% ttl=7d days=mon-fri hours=18-23 on=text,caption" into a reply with its options
+fn parse_trigger_reply(raw: &str) -> Result<TriggerReply, String> {
+    let mut text = raw.trim();
+    let mut reply = TriggerReply::new(String::new());
+    let mut days = None;
+    let mut hours = None;
+
+    // Options trail the reply text, so peel them off from the end
+    while let Some((rest, option)) = text.rsplit_once(' ') {
+        if let Some(percent) = option.strip_prefix('@').and_then(|o| o.strip_suffix('%')) {
+            reply.probability = percent.parse::<u8>()
+                .ok()
+                .filter(|p| (1..=100).contains(p))
+                .ok_or("Probability must be between 1% and 100%")?;
+        } else if let Some(ttl) = option.strip_prefix("ttl=") {
+            let ttl = parse_duration(ttl).ok_or("TTL must look like 30m, 12h, 7d or 2w, at most a year")?;
+            reply.expires_at = Some(Utc::now().timestamp() + ttl);
+        } else if let Some(value) = option.strip_prefix("days=") {
+            days = Some(parse_weekdays(value).ok_or("Days must look like mon-fri or sat,sun")?);
+        } else if let Some(value) = option.strip_prefix("hours=") {
+            hours = Some(parse_hours(value).ok_or("Hours must look like 18-23")?);
+        } else if let Some(value) = option.strip_prefix("on=") {
+            reply.content = value.split(',')
+                .map(ContentKind::parse)
+                .collect::<Option<Vec<_>>>()
+                .ok_or("Content must be a list of text, caption, poll, forward")?;
+        } else {
+            break;
+        }
+        text = rest.trim_end();
+    }
+
+    if days.is_some() || hours.is_some() {
+        let (start_hour, end_hour) = hours.unwrap_or((0, 24));
+        reply.active = Some(ActiveWindow {
+            days: days.unwrap_or_default(),
+            start_hour,
+            end_hour,
+        });
+    }
+
+    reply.reply = text.to_string();
+    if reply.reply.is_empty() {
+        return Err("Reply must not be empty".to_string());
+    }
+
+    Ok(reply)
+}
+
 async fn delete_message_after_delay(bot: Bot, chat_id: ChatId, message_id: MessageId, seconds: u64) {
     tokio::time::sleep(Duration::from_secs(seconds)).await;
     if let Err(e) = bot.delete_message(chat_id, message_id).await {
@@ -1,31 +1,158 @@
 use teloxide::prelude::*;
-use crate::utils::dictionary::{default_reply_frequency, update_reply_frequency};
+use crate::handlers::target::{command_args, explicit_username};
+use crate::utils::dictionary::{
+    default_reply_frequency, get_chat, update_counter_mode, update_reply_frequency, update_reply_policy,
+    update_user_reply_frequency, CounterMode, ReplyPolicy, UserTarget,
+};
+use crate::utils::time::parse_duration;
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /changefrq 4 (every 4th match), /changefrq random 30%, /changefrq interval 10m, /changefrq burst 3 10m, /changefrq count all|matched or /changefrq @username 4 (off to reset)";
 
 pub async fn change_reply_frequency(bot: Bot, msg: Message) -> ResponseResult<()> {
 
     if let Some(msg_text) = msg.text() {
-        let parts: Vec<&str> = msg_text.splitn(2, ' ').collect();
-        if parts.len() < 2 {
-            bot.send_message(msg.chat.id, "Invalid format. Usage: /changefrq 4").await?;
+        let args = command_args(msg_text).trim();
+        if args.is_empty() {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
             return Ok(());
         }
 
         let chat_id = msg.chat.id.0.to_string();
+
+        let (policy, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        if policy == "count" {
+            return change_counter_mode(bot, &msg, chat_id, value.trim()).await;
+        }
+        if matches!(policy, "every" | "random" | "interval" | "burst") {
+            return change_reply_policy(bot, &msg, chat_id, policy, value.trim()).await;
+        }
+
+        // "/changefrq @user 5" overrides the frequency for one member. Replies don't pick a member,
+        // so "/changefrq 5" sent as a reply still sets the chat's frequency
+        if let Some((user, value)) = explicit_username(args) {
+            return change_user_reply_frequency(bot, &msg, chat_id, &user, value).await;
+        }
+
         let mut reply_frq: u32 = default_reply_frequency();
-        if let Ok(msg_text) = parts[1].parse::<u32>() {
+        if let Ok(msg_text) = args.trim_start_matches("all").trim().parse::<u32>() {
             reply_frq = msg_text;
         }
         
         match update_reply_frequency(chat_id, reply_frq) {
             Ok(_) => {
-                bot.send_message(msg.chat.id, "Reply frequency updated").await?;
+                bot.send_message(msg.chat.id, "Reply frequency updated").limited().await?;
             }
             Err(e) => {
                 log::error!("Failed to update reply frequency: {}", e);
-                bot.send_message(msg.chat.id, "Failed to update reply frequency").await?;
+                bot.send_message(msg.chat.id, "Failed to update reply frequency").limited().await?;
             }       
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn change_user_reply_frequency(bot: Bot, msg: &Message, chat_id: String, user: &UserTarget, value: &str) -> ResponseResult<()> {
+    let reply_frq = match value.trim() {
+        "off" | "reset" => None,
+        value => match value.parse::<u32>() {
+            Ok(frq) if frq > 0 => Some(frq),
+            _ => {
+                bot.send_message(msg.chat.id, "Invalid format. Usage: /changefrq @username 4 or /changefrq @username off").limited().await?;
+                return Ok(());
+            }
+        },
+    };
+
+    match update_user_reply_frequency(chat_id, user, reply_frq) {
+        Ok(_) => {
+            let confirmation = match reply_frq {
+                Some(_) => "User reply frequency updated",
+                None => "User reply frequency reset to the chat's",
+            };
+            bot.send_message(msg.chat.id, confirmation).limited().await?;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            bot.send_message(msg.chat.id, format!("Error updating reply frequency: {}", e)).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update user reply frequency: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update reply frequency").limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+// "every 4", "random 30%", "interval 10m" or "burst 3 10m"
+fn parse_reply_policy(policy: &str, value: &str) -> Option<ReplyPolicy> {
+    match policy {
+        "random" => {
+            let percent = value.trim_end_matches('%').parse::<u8>().ok().filter(|p| (1..=100).contains(p))?;
+            Some(ReplyPolicy::Random { percent })
+        }
+        "interval" => Some(ReplyPolicy::MinInterval { seconds: parse_duration(value)? }),
+        "burst" => {
+            let (capacity, refill) = value.split_once(char::is_whitespace)?;
+            let capacity = capacity.parse::<u32>().ok().filter(|c| *c > 0)?;
+            Some(ReplyPolicy::TokenBucket { capacity, refill_seconds: parse_duration(refill)? })
+        }
+        _ => None,
+    }
+}
+
+async fn change_reply_policy(bot: Bot, msg: &Message, chat_id: String, policy: &str, value: &str) -> ResponseResult<()> {
+    let result = if policy == "every" {
+        match value.parse::<u32>() {
+            Ok(reply_frq) if reply_frq > 0 => update_reply_frequency(chat_id.clone(), reply_frq),
+            _ => {
+                bot.send_message(msg.chat.id, USAGE).limited().await?;
+                return Ok(());
+            }
+        }
+    } else {
+        let Some(reply_policy) = parse_reply_policy(policy, value) else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+        update_reply_policy(chat_id.clone(), reply_policy)
+    };
+
+    match result {
+        Ok(_) => {
+            let label = get_chat(&chat_id).map(|chat| chat.reply_policy_label()).unwrap_or_default();
+            bot.send_message(msg.chat.id, format!("Reply policy updated: {}", label)).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update reply policy: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update reply policy").limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+// "count all" advances the counter on every message, "count matched" only on trigger matches
+async fn change_counter_mode(bot: Bot, msg: &Message, chat_id: String, value: &str) -> ResponseResult<()> {
+    let Some(counter_mode) = CounterMode::parse(value) else {
+        bot.send_message(msg.chat.id, USAGE).limited().await?;
+        return Ok(());
+    };
+
+    match update_counter_mode(chat_id, counter_mode) {
+        Ok(_) => {
+            let confirmation = match counter_mode {
+                CounterMode::All => "Reply counter now counts every message",
+                CounterMode::Matched => "Reply counter now counts matched messages only",
+            };
+            bot.send_message(msg.chat.id, confirmation).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update counter mode: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update counter mode").limited().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use teloxide::prelude::*;
+use crate::handlers::target::command_args;
+use crate::utils::dictionary::{copy_chat_triggers, ConflictMode};
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /copytriggers from_chat_id [to_chat_id] [skip|overwrite|rename] [trigger1, trigger2]";
+
+pub async fn copy_triggers(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let Some(text) = msg.text() else {
+        return Ok(());
+    };
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    let mut args = command_args(text).trim();
+
+    let (word, rest) = split_word(args);
+    let Ok(from_chat_id) = word.parse::<i64>() else {
+        bot.send_message(msg.chat.id, USAGE).limited().await?;
+        return Ok(());
+    };
+    args = rest;
+
+    // Destination defaults to the chat the command is sent in
+    let mut to_chat_id = msg.chat.id.0;
+    let (word, rest) = split_word(args);
+    if let Ok(chat_id) = word.parse::<i64>() {
+        to_chat_id = chat_id;
+        args = rest;
+    }
+
+    let mut mode = ConflictMode::Skip;
+    let (word, rest) = split_word(args);
+    if let Some(parsed) = ConflictMode::parse(word) {
+        mode = parsed;
+        args = rest;
+    }
+
+    let selected: Vec<String> = args
+        .split(',')
+        .map(|trigger| trigger.trim().to_lowercase())
+        .filter(|trigger| !trigger.is_empty())
+        .collect();
+
+    if from_chat_id == to_chat_id {
+        bot.send_message(msg.chat.id, "Source and destination chats must differ").limited().await?;
+        return Ok(());
+    }
+
+    for chat_id in [from_chat_id, to_chat_id] {
+        let is_admin = match bot.get_chat_member(ChatId(chat_id), user.id).await {
+            Ok(member) => member.is_privileged(),
+            Err(e) => {
+                log::warn!("Failed to check admin rights in chat {}: {}", chat_id, e);
+                false
+            }
+        };
+
+        if !is_admin {
+            bot.send_message(msg.chat.id, format!("You need to be an admin in chat {} to copy triggers", chat_id)).limited().await?;
+            return Ok(());
+        }
+    }
+
+    match copy_chat_triggers(&from_chat_id.to_string(), to_chat_id.to_string(), &selected, mode) {
+        Ok(report) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Copied {} triggers, overwrote {}, renamed {}, skipped {}",
+                    report.copied, report.overwritten, report.renamed, report.skipped
+                )
+            ).limited().await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("Error copying triggers: {}", e)).limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn split_word(args: &str) -> (&str, &str) {
+    let (word, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    (word, rest.trim())
+}
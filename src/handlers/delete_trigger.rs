@@ -1,6 +1,12 @@
 use teloxide::{prelude::*, types::MessageId};
-use crate::utils::dictionary::{delete_user_trigger, delete_common_trigger};
+use crate::utils::dictionary::{
+    delete_user_trigger, delete_common_trigger, delete_user_sticker_trigger, delete_common_sticker_trigger,
+    delete_user_mention_trigger,
+};
+use crate::handlers::target::{command_args, mention_keyword, parse_target};
+use crate::utils::sticker::sticker_key;
 use std::time::Duration;
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn delete_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
     if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
@@ -8,43 +14,91 @@ pub async fn delete_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
     }
 
     if let Some(text) = msg.text() {
-        let parts: Vec<&str> = text.splitn(3, ' ').collect();
-        if parts.len() < 3 {
-            bot.send_message(msg.chat.id, "Invalid format. Usage: /delete tg_username trigger").await?;
+        let Some((target, trigger)) = parse_target(&msg, command_args(text)) else {
+            bot.send_message(msg.chat.id, "Invalid format. Usage: /delete @username trigger (or reply to their message)").limited().await?;
+            return Ok(());
+        };
+
+        let chat_id = msg.chat.id.0.to_string();
+
+        // Replying to a sticker with "/delete all [emoji|set]" removes the sticker trigger
+        let replied_sticker = msg.reply_to_message().and_then(|m| m.sticker());
+        if let Some(key) = replied_sticker.and_then(|sticker| sticker_key(sticker, trigger)) {
+            let result = match &target {
+                None => delete_common_sticker_trigger(chat_id, key.clone()),
+                Some(user) => delete_user_sticker_trigger(chat_id, user, key.clone()),
+            };
+
+            match result {
+                Ok(_) => {
+                    let success_msg = bot.send_message(
+                        msg.chat.id,
+                        format!("Deleted sticker '{}' from triggers dictionary!", key)
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
+                }
+                Err(e) => {
+                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).limited().await?;
+                }
+            }
+            return Ok(());
+        }
+
+        if trigger.is_empty() {
+            bot.send_message(msg.chat.id, "Invalid format. Usage: /delete @username trigger (or reply to their message)").limited().await?;
             return Ok(());
         }
 
-        let tg_username = parts[1].trim_start_matches('@').to_string();
-        let trigger = parts[2];
+        if let (Some(keyword), Some(user)) = (mention_keyword(trigger), &target) {
+            match delete_user_mention_trigger(chat_id, user, keyword.to_string()) {
+                Ok(_) => {
+                    let success_msg = bot.send_message(
+                        msg.chat.id,
+                        format!("Deleted '{}' from triggers dictionary!", trigger)
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
+                }
+                Err(e) => {
+                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).limited().await?;
+                }
+            }
+            return Ok(());
+        }
 
-        let chat_id = msg.chat.id.0.to_string();
-        
-        if tg_username == "all" {
-            match delete_common_trigger(chat_id, trigger.to_string()) {
+        if let Some(user) = &target {
+            match delete_user_trigger(chat_id, user, trigger.to_string()) {
                 Ok(_) => {
                     let success_msg = bot.send_message(
                         msg.chat.id,
                         format!("Deleted '{}' from triggers dictionary!", trigger)
-                    ).await?;
-                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
 
                 }
                 Err(e) => {
-                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).await?;
+                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).limited().await?;
                 }
             }
         } else {
-            match delete_user_trigger(chat_id, tg_username, trigger.to_string()) {
+            match delete_common_trigger(chat_id, trigger.to_string()) {
                 Ok(_) => {
                     let success_msg = bot.send_message(
                         msg.chat.id,
                         format!("Deleted '{}' from triggers dictionary!", trigger)
-                    ).await?;
-                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    ).limited().await?;
+                    if let Some(success_msg) = success_msg {
+                        tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                    }
 
                 }
                 Err(e) => {
-                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).await?;
+                    bot.send_message(msg.chat.id, format!("Error deleting trigger: {}", e)).limited().await?;
                 }
             }
         }
@@ -0,0 +1,58 @@
+use teloxide::{prelude::*, types::MessageId};
+use crate::handlers::target::{command_args, parse_target};
+use crate::utils::dictionary::edit_trigger_reply;
+use std::time::Duration;
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /edit @username trigger=new reply (or /edit all trigger=new reply)";
+
+pub async fn edit_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
+    if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+        println!("Failed to delete command message: {}", e);
+    }
+
+    if let Some(text) = msg.text() {
+        let Some((target, trigger)) = parse_target(&msg, command_args(text)) else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+
+        let Some((trigger_key, reply)) = trigger.split_once('=') else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+
+        let trigger_key = trigger_key.trim().to_string();
+        let reply = reply.trim().to_string();
+        if trigger_key.is_empty() || reply.is_empty() {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        }
+
+        let chat_id = msg.chat.id.0.to_string();
+
+        match edit_trigger_reply(chat_id, target.as_ref(), trigger_key.clone(), reply) {
+            Ok(_) => {
+                let success_msg = bot.send_message(
+                    msg.chat.id,
+                    format!("Updated reply for '{}'!", trigger_key)
+                ).limited().await?;
+                if let Some(success_msg) = success_msg {
+                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                }
+            }
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("Error editing trigger: {}", e)).limited().await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn delete_message_after_delay(bot: Bot, chat_id: ChatId, message_id: MessageId, seconds: u64) {
+    tokio::time::sleep(Duration::from_secs(seconds)).await;
+    if let Err(e) = bot.delete_message(chat_id, message_id).await {
+        println!("Failed to delete message: {}", e);
+    }
+}
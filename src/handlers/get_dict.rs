@@ -2,6 +2,7 @@ use std::fs;
 use std::env;
 use teloxide::prelude::*;
 use teloxide::types::InputFile;
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn get_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
     let file_path = if env::var("APP_ENV").unwrap() == "test" {
@@ -15,11 +16,11 @@ pub async fn get_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
             let input_file = InputFile::memory(content.into_bytes())
                 .file_name("dictionaries.json");
 
-            bot.send_document(msg.chat.id, input_file).await?;
+            bot.send_document(msg.chat.id, input_file).limited().await?;
         }
         Err(e) => {
             log::error!("Failed to read dictionaries.json: {}", e);
-            bot.send_message(msg.chat.id, "Failed to read dictionaries file").await?;
+            bot.send_message(msg.chat.id, "Failed to read dictionaries file").limited().await?;
         }
     }
     Ok(())
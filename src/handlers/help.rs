@@ -1,11 +1,12 @@
 use teloxide::{prelude::*, types::Message};
 use teloxide::utils::command::BotCommands;
 use crate::commands::Command;
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn help(bot: Bot, msg: Message) -> ResponseResult<()> {
     bot.send_message(
         msg.chat.id,
         Command::descriptions().to_string()
-    ).await?;
+    ).limited().await?;
     Ok(())
 }
\ No newline at end of file
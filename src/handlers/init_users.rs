@@ -1,8 +1,8 @@
 use teloxide::{prelude::*, types::{Message, ChatMemberKind}};
-use std::collections::HashMap;
 use crate::{
-    utils::dictionary::{DICTIONARY, Chat, User}
+    utils::dictionary::{DICTIONARY, Chat, UserTarget}
 };
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
     let chat_id = msg.chat.id.0.to_string();
@@ -20,7 +20,7 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
                      - Delete Messages\n\
                      - Send Messages\n\
                      - Read Messages"
-                ).await?;
+                ).limited().await?;
             }
         }
         _ => {
@@ -31,7 +31,7 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
                  - Delete Messages\n\
                  - Send Messages\n\
                  - Read Messages"
-            ).await?;
+            ).limited().await?;
             return Ok(());
         }
     }
@@ -40,16 +40,7 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
     let admins = bot.get_chat_administrators(msg.chat.id).await?;
 
     // Prepare the data outside of the mutex lock
-    let users_data: Vec<_> = admins.into_iter().map(|member| {
-        let user = member.user;
-        let username = user.username.unwrap_or_default();
-        let fullname = format!("{} {}",
-                               user.first_name,
-                               user.last_name.unwrap_or_default()
-        ).trim().to_string();
-
-        (username, fullname)
-    }).collect();
+    let users_data: Vec<UserTarget> = admins.iter().map(|member| UserTarget::from(&member.user)).collect();
 
     // Update dictionary in a separate scope to ensure MutexGuard is dropped
     let update_result = {
@@ -59,16 +50,7 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
 
         if let Some(manager) = lock.as_mut() {
             // Get or create chat entry
-            let chat = manager.chats.entry(chat_id.clone()).or_insert_with(|| {
-                Chat {
-                    message_counter: 0,
-                    reply_frequency: crate::utils::dictionary::default_reply_frequency(),
-                    roast_level: crate::utils::dictionary::default_roast_level(),
-                    name: chat_title.clone(),
-                    users: HashMap::new(),
-                    common_replies: HashMap::new(),
-                }
-            });
+            let chat = manager.chats.entry(chat_id.clone()).or_insert_with(|| Chat::new(chat_title.clone()));
 
             // Update chat name
             chat.name = chat_title;
@@ -77,25 +59,15 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
                 chat.reply_frequency = crate::utils::dictionary::default_reply_frequency();
             }
 
-            // Add or update members
-            for (username, fullname) in users_data {
-                if !username.is_empty() {  // Only add users with usernames
-                    let user_entry = chat.users.entry(username).or_insert_with(|| {
-                        User {
-                            fullname: fullname.clone(),
-                            replies: HashMap::new(),
-                        }
-                    });
-                }
+            // Add or update members, keyed by user id
+            for target in &users_data {
+                chat.user_entry(target);
             }
 
             let users_count = chat.users.len();
             manager.save().map(|_| users_count)
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Dictionary not initialized"
-            ))
+            Err(std::io::Error::other("Dictionary not initialized"))
         }
     };
 
@@ -105,13 +77,13 @@ pub async fn init_users(bot: Bot, msg: Message) -> ResponseResult<()> {
             bot.send_message(
                 msg.chat.id,
                 format!("Successfully initialized {} users", users_count)
-            ).await?;
+            ).limited().await?;
         }
         Err(e) => {
             bot.send_message(
                 msg.chat.id,
                 format!("Failed to update dictionary: {}", e)
-            ).await?;
+            ).limited().await?;
         }
     }
 
@@ -0,0 +1,204 @@
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use crate::handlers::target::{command_args, parse_target};
+use crate::utils::dictionary::{get_chat, Chat, TriggerScope};
+use crate::utils::rate_limit::RateLimitExt;
+
+const PAGE_SIZE: usize = 10;
+const MAX_FILTER_BUTTONS: usize = 6;
+const CALLBACK_PREFIX: &str = "list";
+// Keeps a full page well under Telegram's 4096 character limit
+const MAX_KEY_CHARS: usize = 40;
+const MAX_REPLY_CHARS: usize = 80;
+
+// Which triggers a page shows; encoded into callback data as "*", "all" or a user key
+#[derive(Debug, Clone, PartialEq)]
+enum ListFilter {
+    Everything,
+    Common,
+    User(String),
+}
+
+impl ListFilter {
+    fn encode(&self) -> String {
+        match self {
+            ListFilter::Everything => "*".to_string(),
+            ListFilter::Common => "all".to_string(),
+            ListFilter::User(user_key) => user_key.clone(),
+        }
+    }
+
+    fn decode(raw: &str) -> Self {
+        match raw {
+            "*" => ListFilter::Everything,
+            "all" => ListFilter::Common,
+            user_key => ListFilter::User(user_key.to_string()),
+        }
+    }
+
+    fn matches(&self, scope: &TriggerScope) -> bool {
+        match (self, scope) {
+            (ListFilter::Everything, _) => true,
+            (ListFilter::Common, TriggerScope::Common) => true,
+            (ListFilter::User(user_key), TriggerScope::User(scope_key) | TriggerScope::Mention(scope_key)) => user_key == scope_key,
+            _ => false,
+        }
+    }
+}
+
+pub async fn list_triggers(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0.to_string();
+
+    let Some(chat) = get_chat(&chat_id) else {
+        bot.send_message(msg.chat.id, "No triggers in this chat yet").limited().await?;
+        return Ok(());
+    };
+
+    let args = msg.text().map(command_args).unwrap_or_default();
+    let filter = match parse_target(&msg, args) {
+        Some((None, _)) => ListFilter::Common,
+        Some((Some(target), _)) => match chat.find_user_key(&target) {
+            Some(user_key) => ListFilter::User(user_key),
+            None => {
+                bot.send_message(msg.chat.id, "User not found in this chat").limited().await?;
+                return Ok(());
+            }
+        },
+        None => ListFilter::Everything,
+    };
+
+    let (text, keyboard) = render_page(&chat, &filter, 0);
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(keyboard)
+        .limited()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn handle_list_callback(bot: Bot, q: CallbackQuery) -> ResponseResult<()> {
+    let Some((filter, page)) = q.data.as_deref().and_then(parse_callback_data) else {
+        bot.answer_callback_query(q.id).await?;
+        return Ok(());
+    };
+    let Some(message) = q.message.as_ref() else {
+        bot.answer_callback_query(q.id).await?;
+        return Ok(());
+    };
+
+    let chat_id = message.chat().id;
+    match get_chat(&chat_id.0.to_string()) {
+        Some(chat) => {
+            let (text, keyboard) = render_page(&chat, &filter, page);
+            if let Err(e) = bot.edit_message_text(chat_id, message.id(), text)
+                .reply_markup(keyboard)
+                .await
+            {
+                // Telegram rejects edits that change nothing, e.g. pressing the current filter again
+                log::debug!("Failed to update trigger list: {}", e);
+            }
+            bot.answer_callback_query(q.id).await?;
+        }
+        None => {
+            bot.answer_callback_query(q.id).text("No triggers in this chat yet").await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_callback_data(data: &str) -> Option<(ListFilter, usize)> {
+    let mut parts = data.splitn(3, ':');
+    if parts.next()? != CALLBACK_PREFIX {
+        return None;
+    }
+    let filter = ListFilter::decode(parts.next()?);
+    let page = parts.next()?.parse().ok()?;
+    Some((filter, page))
+}
+
+fn callback_data(filter: &ListFilter, page: usize) -> String {
+    format!("{}:{}:{}", CALLBACK_PREFIX, filter.encode(), page)
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let shortened: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+fn render_page(chat: &Chat, filter: &ListFilter, page: usize) -> (String, InlineKeyboardMarkup) {
+    let mut triggers: Vec<_> = chat.triggers()
+        .into_iter()
+        .filter(|trigger| filter.matches(&trigger.scope))
+        .collect();
+    triggers.sort_by(|a, b| a.trigger.cmp(b.trigger));
+
+    let pages = triggers.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let title = match filter {
+        ListFilter::Everything => "All triggers".to_string(),
+        ListFilter::Common => "Common triggers".to_string(),
+        ListFilter::User(user_key) => format!("Triggers of {}", chat.user_label(user_key)),
+    };
+
+    let mut text = format!("{} ({}), page {}/{}\n\n", title, triggers.len(), page + 1, pages);
+    if triggers.is_empty() {
+        text.push_str("Nothing here yet");
+    }
+    for trigger in triggers.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+        let kind = if trigger.sticker { "sticker " } else { "" };
+        let key = match (&trigger.scope, trigger.trigger.is_empty()) {
+            (TriggerScope::Mention(_), true) => "mention".to_string(),
+            (TriggerScope::Mention(_), false) => format!("mention:{}", trigger.trigger),
+            _ => trigger.trigger.clone(),
+        };
+        let reply = shorten(&trigger.reply.reply, MAX_REPLY_CHARS);
+        text.push_str(&format!("• {}{} → {}", kind, shorten(&key, MAX_KEY_CHARS), reply));
+
+        if trigger.reply.probability < 100 {
+            text.push_str(&format!(" @{}%", trigger.reply.probability));
+        }
+        if *filter == ListFilter::Everything {
+            if let TriggerScope::User(user_key) | TriggerScope::Mention(user_key) = &trigger.scope {
+                text.push_str(&format!(" ({})", chat.user_label(user_key)));
+            }
+        }
+        text.push('\n');
+    }
+
+    let mut navigation = Vec::new();
+    if page > 0 {
+        navigation.push(InlineKeyboardButton::callback("◀ Prev", callback_data(filter, page - 1)));
+    }
+    if page + 1 < pages {
+        navigation.push(InlineKeyboardButton::callback("Next ▶", callback_data(filter, page + 1)));
+    }
+
+    let mut filters = vec![
+        InlineKeyboardButton::callback("Everyone", callback_data(&ListFilter::Everything, 0)),
+        InlineKeyboardButton::callback("Common", callback_data(&ListFilter::Common, 0)),
+    ];
+    let mut users: Vec<_> = chat.users
+        .iter()
+        .filter(|(_, user)| !user.replies.is_empty() || !user.sticker_replies.is_empty() || !user.mention_replies.is_empty())
+        .map(|(user_key, _)| user_key.clone())
+        .collect();
+    users.sort();
+    filters.extend(users.into_iter().take(MAX_FILTER_BUTTONS).map(|user_key| {
+        InlineKeyboardButton::callback(chat.user_label(&user_key), callback_data(&ListFilter::User(user_key), 0))
+    }));
+
+    let mut keyboard = InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new());
+    if !navigation.is_empty() {
+        keyboard = keyboard.append_row(navigation);
+    }
+    for row in filters.chunks(2) {
+        keyboard = keyboard.append_row(row.to_vec());
+    }
+
+    (text, keyboard)
+}
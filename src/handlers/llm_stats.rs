@@ -0,0 +1,23 @@
+use teloxide::prelude::*;
+use crate::utils::llm::provider_metrics;
+use crate::utils::rate_limit::RateLimitExt;
+
+pub async fn llm_stats(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let metrics = provider_metrics();
+    if metrics.is_empty() {
+        bot.send_message(msg.chat.id, "No AI requests since startup").limited().await?;
+        return Ok(());
+    }
+
+    let mut report = String::from("AI providers since startup:\n");
+    for (name, m) in metrics {
+        report.push_str(&format!("{}: {} ok, {} failed, {} timed out", name, m.successes, m.failures, m.timeouts));
+        if m.successes > 0 {
+            report.push_str(&format!(", avg {}ms", m.total_latency_ms / m.successes as u64));
+        }
+        report.push('\n');
+    }
+
+    bot.send_message(msg.chat.id, report).limited().await?;
+    Ok(())
+}
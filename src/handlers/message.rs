@@ -0,0 +1,238 @@
+use teloxide::prelude::*;
+use teloxide::sugar::request::RequestReplyExt;
+use crate::utils::dictionary::{
+    DICTIONARY, ContentKind, DialogMatch, TriggerHit, UserTarget, continue_chat_dialog, count_chat_message, get_chat,
+    get_dictionary_response, get_mention_dictionary_response, get_sticker_dictionary_response, get_unfired_dictionary_response,
+    get_user_profile, is_chat_quiet, match_chat_dialog, remember_message_triggers, resolve_chat_user,
+};
+use crate::handlers::target::mention_targets;
+use crate::utils::roast::Roaster;
+use crate::utils::templates::template_roast;
+use crate::utils::history::{recent_messages, record_message, HistoryLine, MAX_LINE_CHARS};
+use crate::utils::sticker::sticker_keys;
+use crate::utils::rate_limit::RateLimitExt;
+
+// Text a trigger can be matched against, together with where it came from
+fn message_content(msg: &Message) -> Option<(&str, ContentKind)> {
+    if msg.forward_origin().is_some() {
+        return msg.text().or(msg.caption()).map(|text| (text, ContentKind::Forward));
+    }
+
+    if let Some(text) = msg.text() {
+        Some((text, ContentKind::Text))
+    } else if let Some(caption) = msg.caption() {
+        Some((caption, ContentKind::Caption))
+    } else {
+        msg.poll().map(|poll| (poll.question.as_str(), ContentKind::Poll))
+    }
+}
+
+// How a message author appears in roast prompts
+fn author_name(user: &teloxide::types::User) -> String {
+    user.username.as_ref().map(|u| format!("@{}", u)).unwrap_or_else(|| user.full_name())
+}
+
+// The message being replied to, so a roast can take on the whole exchange
+fn replied_line(msg: &Message) -> Option<HistoryLine> {
+    let parent = msg.reply_to_message()?;
+    let text = parent.text().or(parent.caption())?;
+    let author = parent.from.as_ref().map(author_name).unwrap_or_else(|| "someone".to_string());
+
+    Some(HistoryLine { message_id: parent.id.0, author, text: text.chars().take(MAX_LINE_CHARS).collect() })
+}
+
+// Records the hit and returns whether to reply, or None if the trigger already fired on the edited message
+fn claim_trigger_hit(chat_id: &String, edited_message_id: Option<i32>, user_id: u64, hit: &TriggerHit) -> Option<bool> {
+    let mut lock = DICTIONARY.lock().ok()?;
+    let manager = lock.as_mut()?;
+
+    if let Some(message_id) = edited_message_id {
+        if !manager.mark_fired(chat_id, message_id, hit) {
+            return None;
+        }
+    }
+
+    manager.record_hit(chat_id, hit, user_id);
+    let should_reply = manager.should_reply_to_message(chat_id, user_id);
+    manager.save().ok();
+    Some(should_reply)
+}
+
+// Replies with a matched trigger, honoring the chat's reply frequency
+async fn send_trigger_reply(bot: &Bot, msg: &Message, chat_id: &String, user_id: u64, hit: TriggerHit) -> ResponseResult<()> {
+    if claim_trigger_hit(chat_id, None, user_id, &hit) == Some(true) {
+        bot.send_message(msg.chat.id, hit.reply)
+            .reply_to(msg)
+            .limited()
+            .await?;
+    }
+
+    Ok(())
+}
+
+// Dialog replies always go out, and the bot's message becomes the one to answer next
+async fn send_dialog_reply(bot: &Bot, msg: &Message, chat_id: &String, user_id: u64, dialog_match: DialogMatch) -> ResponseResult<()> {
+    let Some(sent) = bot.send_message(msg.chat.id, dialog_match.reply)
+        .reply_to(msg)
+        .limited()
+        .await?
+    else {
+        return Ok(());
+    };
+
+    if let Some(step) = dialog_match.next_step {
+        if let Err(e) = continue_chat_dialog(chat_id, user_id, dialog_match.dialog, step, sent.id.0) {
+            log::error!("Failed to save dialog state: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_message(bot: Bot, msg: Message, ai_roaster: Option<Roaster>) -> ResponseResult<()> {
+    // Claims username keyed entries by id and follows username changes
+    if let Some(user) = msg.from.as_ref() {
+        if let Err(e) = resolve_chat_user(&msg.chat.id.0.to_string(), &UserTarget::from(user)) {
+            log::warn!("Failed to resolve chat user: {}", e);
+        }
+        count_chat_message(&msg.chat.id.0.to_string(), user.id.0);
+
+        // Remember what people write so roasts can see the conversation
+        if let Some((text, ContentKind::Text | ContentKind::Caption)) = message_content(&msg) {
+            record_message(msg.chat.id.0, msg.id.0, &author_name(user), text);
+        }
+
+        // Every trigger the original contains, so an edit only fires ones it added
+        if let Some((text, _)) = message_content(&msg) {
+            remember_message_triggers(&msg.chat.id.0.to_string(), user.id.0, msg.id.0, text);
+        }
+    }
+
+    // Quiet hours and /shutup silence triggers and roasts alike
+    if is_chat_quiet(&msg.chat.id.0.to_string()) {
+        return Ok(());
+    }
+
+    if let (Some(sticker), Some(user)) = (msg.sticker(), msg.from.as_ref()) {
+        let chat_id = msg.chat.id.0.to_string();
+
+        if let Some(hit) = get_sticker_dictionary_response(chat_id.clone(), user.id.0, &sticker_keys(sticker)) {
+            send_trigger_reply(&bot, &msg, &chat_id, user.id.0, hit).await?;
+        }
+        return Ok(());
+    }
+
+    if let Some((text, kind)) = message_content(&msg) {
+        if let Some(user) = msg.from.as_ref() {
+            let chat_id = msg.chat.id.0.to_string();
+            let username = user.username.clone().unwrap_or_default();
+
+            if kind == ContentKind::Text {
+                let replied_message_id = msg.reply_to_message().map(|m| m.id.0);
+                match match_chat_dialog(&chat_id, user.id.0, replied_message_id, text) {
+                    Ok(Some(dialog_match)) => {
+                        return send_dialog_reply(&bot, &msg, &chat_id, user.id.0, dialog_match).await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Failed to match dialog: {}", e),
+                }
+            }
+
+            if let Some(hit) = get_dictionary_response(chat_id.clone(), user.id.0, text.to_string(), kind) {
+                return send_trigger_reply(&bot, &msg, &chat_id, user.id.0, hit).await;
+            }
+
+            let targets = mention_targets(&msg);
+            if let Some(hit) = get_mention_dictionary_response(chat_id.clone(), user.id.0, &targets, text.to_string(), kind) {
+                return send_trigger_reply(&bot, &msg, &chat_id, user.id.0, hit).await;
+            }
+
+            // Only roast what the sender wrote themselves
+            if !matches!(kind, ContentKind::Text | ContentKind::Caption) {
+                return Ok(());
+            }
+
+            // let roast_chance = 1.0; // 100% chance to roast
+            // let roast_chance = 0.5; // 50% chance to roast
+            // let roast_chance = 0.2; // 20% chance to roast
+            let roast_chance = 0.15; // 15% chance to roast
+            // let roast_chance = 0.1; // 10% chance to roast
+            let (chat_roast_level, context_window) = if let Ok(lock) = DICTIONARY.lock() {
+                if let Some(manager) = lock.as_ref() {
+                    (manager.get_roast_level(chat_id.clone()), manager.get_context_window(&chat_id))
+                } else {
+                    (3u8, 0)
+                }
+            } else {
+                (3u8, 0)
+            };
+
+            if rand::random::<f32>() < roast_chance && text.len() > 50 {
+                let roast = if let Some(roaster) = &ai_roaster {
+                    let replied = replied_line(&msg);
+                    let mut recent = recent_messages(msg.chat.id.0, msg.id.0, context_window);
+                    if let Some(replied) = &replied {
+                        recent.retain(|line| line.message_id != replied.message_id);
+                    }
+                    let profile = get_user_profile(&chat_id, &UserTarget::from(user));
+                    match roaster.generate_roast(text, &username, chat_roast_level, &recent, replied.as_ref(), &profile).await {
+                        Ok(ai_roast) => {
+                            Some(ai_roast)
+                        }
+                        Err(e) => {
+                            log::warn!("AI roast failed: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                // Falls back to the offline templates when no AI provider is set up or answered
+                let roast_message = roast.unwrap_or_else(|| {
+                    let name = user.username.as_ref().map(|u| format!("@{}", u)).unwrap_or_else(|| user.first_name.clone());
+                    template_roast(text, &name, chat_roast_level)
+                });
+
+                bot.send_message(msg.chat.id, roast_message)
+                    .reply_to(&msg)
+                    .limited()
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Edits only fire triggers the original message didn't, and only in chats that opted in
+pub async fn handle_edited_message(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0.to_string();
+    let Some(chat) = get_chat(&chat_id).filter(|chat| chat.watch_edits && !chat.is_quiet()) else {
+        return Ok(());
+    };
+    let (Some((text, kind)), Some(user)) = (message_content(&msg), msg.from.as_ref()) else {
+        return Ok(());
+    };
+    let Some(hit) = get_unfired_dictionary_response(chat_id.clone(), user.id.0, msg.id.0, text.to_string(), kind) else {
+        return Ok(());
+    };
+
+    let Some(should_reply) = claim_trigger_hit(&chat_id, Some(msg.id.0), user.id.0, &hit) else {
+        return Ok(());
+    };
+
+    if let Some(edit_reply) = chat.edit_reply {
+        bot.send_message(msg.chat.id, edit_reply)
+            .reply_to(&msg)
+            .limited()
+            .await?;
+    }
+    if should_reply {
+        bot.send_message(msg.chat.id, hit.reply)
+            .reply_to(&msg)
+            .limited()
+            .await?;
+    }
+
+    Ok(())
+}
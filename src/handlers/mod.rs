@@ -3,7 +3,22 @@ pub(crate) mod help;
 pub(crate) mod init_users;
 pub(crate) mod add_trigger;
 pub(crate) mod delete_trigger;
+pub(crate) mod edit_trigger;
+pub(crate) mod rename_trigger;
+pub(crate) mod copy_triggers;
+pub(crate) mod packs;
 pub(crate) mod get_dict;
 pub(crate) mod set_dict;
 pub(crate) mod change_reply_frequency;
-pub(crate) mod set_roast_level;
\ No newline at end of file
+pub(crate) mod set_roast_level;
+pub(crate) mod set_roast_context;
+pub(crate) mod profile;
+pub(crate) mod set_timezone;
+pub(crate) mod watch_edits;
+pub(crate) mod quiet;
+pub(crate) mod settings;
+pub(crate) mod llm_stats;
+pub(crate) mod trigger_stats;
+pub(crate) mod list_triggers;
+pub(crate) mod message;
+pub(crate) mod target;
\ No newline at end of file
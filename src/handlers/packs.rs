@@ -0,0 +1,70 @@
+use teloxide::prelude::*;
+use crate::handlers::target::command_args;
+use crate::utils::dictionary::{get_chat, get_packs, install_chat_pack, uninstall_chat_pack};
+use crate::utils::rate_limit::RateLimitExt;
+
+pub async fn list_packs(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let packs = get_packs();
+    if packs.is_empty() {
+        bot.send_message(msg.chat.id, "No trigger packs available. Upload them with /setdict").limited().await?;
+        return Ok(());
+    }
+
+    let installed = get_chat(&msg.chat.id.0.to_string())
+        .map(|chat| chat.installed_packs)
+        .unwrap_or_default();
+
+    let mut names: Vec<_> = packs.keys().collect();
+    names.sort();
+
+    let mut text = String::from("Trigger packs:\n");
+    for name in names {
+        let pack = &packs[name];
+        let marker = if installed.contains(name) { "✅" } else { "▫️" };
+        text.push_str(&format!("{} {} ({} triggers)", marker, name, pack.replies.len()));
+        if !pack.description.is_empty() {
+            text.push_str(&format!(" — {}", pack.description));
+        }
+        text.push('\n');
+    }
+    text.push_str("\nUse /installpack name or /uninstallpack name");
+
+    bot.send_message(msg.chat.id, text).limited().await?;
+    Ok(())
+}
+
+pub async fn install_pack(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let name = msg.text().map(command_args).unwrap_or_default().trim();
+    if name.is_empty() {
+        bot.send_message(msg.chat.id, "Invalid format. Usage: /installpack name").limited().await?;
+        return Ok(());
+    }
+
+    match install_chat_pack(msg.chat.id.0.to_string(), name) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, format!("Installed pack '{}'", name)).limited().await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("Error installing pack: {}", e)).limited().await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn uninstall_pack(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let name = msg.text().map(command_args).unwrap_or_default().trim();
+    if name.is_empty() {
+        bot.send_message(msg.chat.id, "Invalid format. Usage: /uninstallpack name").limited().await?;
+        return Ok(());
+    }
+
+    match uninstall_chat_pack(msg.chat.id.0.to_string(), name) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, format!("Uninstalled pack '{}'", name)).limited().await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("Error uninstalling pack: {}", e)).limited().await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use teloxide::prelude::*;
+use crate::handlers::target::{command_args, parse_member_target};
+use crate::utils::dictionary::{
+    add_user_profile_note, clear_user_profile, get_user_profile, UserTarget, MAX_PROFILE_NOTES, MAX_PROFILE_NOTE_CHARS,
+};
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /profile @username note (adds a note), /profile @username (shows it), /profile (shows yours) or /profile clear (clears yours)";
+
+fn target_label(target: &UserTarget) -> String {
+    match target {
+        UserTarget::Member { username: Some(username), .. } | UserTarget::Username(username) => format!("@{}", username),
+        UserTarget::Member { fullname, .. } => fullname.clone(),
+    }
+}
+
+pub async fn profile(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let args = msg.text().map(command_args).unwrap_or_default().trim();
+    let chat_id = msg.chat.id.0.to_string();
+    let Some(sender) = msg.from.as_ref().map(UserTarget::from) else {
+        return Ok(());
+    };
+
+    // Members can only clear their own profile
+    if args == "clear" {
+        match clear_user_profile(&chat_id, &sender) {
+            Ok(true) => {
+                bot.send_message(msg.chat.id, "Your profile is cleared").limited().await?;
+            }
+            Ok(false) => {
+                bot.send_message(msg.chat.id, "Your profile is already empty").limited().await?;
+            }
+            Err(e) => {
+                log::error!("Failed to clear profile: {}", e);
+                bot.send_message(msg.chat.id, "Failed to clear profile").limited().await?;
+            }
+        }
+        return Ok(());
+    }
+
+    // A bare first word is part of the note, so only "@user" or a reply names someone else
+    let (target, note) = match parse_member_target(&msg, args) {
+        Some((target, note)) => (target, note.trim()),
+        None if args.is_empty() => (sender, ""),
+        None => {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        }
+    };
+
+    if note.is_empty() {
+        return show_profile(bot, &msg, &chat_id, &target).await;
+    }
+
+    if note.chars().count() > MAX_PROFILE_NOTE_CHARS {
+        bot.send_message(msg.chat.id, format!("Note is too long, keep it under {} characters", MAX_PROFILE_NOTE_CHARS)).limited().await?;
+        return Ok(());
+    }
+
+    match add_user_profile_note(chat_id, &target, note.to_string()) {
+        Ok(_) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Noted for {} (only the last {} notes are kept)", target_label(&target), MAX_PROFILE_NOTES),
+            )
+                .limited()
+                .await?;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            bot.send_message(msg.chat.id, format!("{}: {}", e, target_label(&target))).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to add profile note: {}", e);
+            bot.send_message(msg.chat.id, "Failed to add profile note").limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn show_profile(bot: Bot, msg: &Message, chat_id: &String, target: &UserTarget) -> ResponseResult<()> {
+    let notes = get_user_profile(chat_id, target);
+    let text = if notes.is_empty() {
+        format!("No profile notes for {}", target_label(target))
+    } else {
+        let lines: Vec<String> = notes.iter().enumerate().map(|(i, note)| format!("{}. {}", i + 1, note)).collect();
+        format!("Profile of {}:\n{}", target_label(target), lines.join("\n"))
+    };
+
+    bot.send_message(msg.chat.id, text).limited().await?;
+    Ok(())
+}
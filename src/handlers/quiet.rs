@@ -0,0 +1,85 @@
+use teloxide::prelude::*;
+use crate::handlers::target::command_args;
+use crate::utils::dictionary::{update_quiet_hours, update_silenced_until, ActiveWindow};
+use crate::utils::rate_limit::RateLimitExt;
+use crate::utils::time::{format_duration, parse_duration, parse_hours};
+use chrono::Utc;
+
+const DEFAULT_SHUTUP_SECONDS: i64 = 30 * 60;
+
+pub async fn quiet_hours(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let args = msg.text().map(command_args).unwrap_or_default().trim();
+    let chat_id = msg.chat.id.0.to_string();
+
+    let quiet_hours = if args == "off" {
+        None
+    } else {
+        let Some((start_hour, end_hour)) = parse_hours(args) else {
+            bot.send_message(msg.chat.id, "Invalid format. Usage: /quiethours 23-8 or /quiethours off").limited().await?;
+            return Ok(());
+        };
+        Some(ActiveWindow { days: Vec::new(), start_hour, end_hour })
+    };
+
+    let confirmation = match &quiet_hours {
+        Some(window) => format!("Quiet hours set to {}:00-{}:00", window.start_hour, window.end_hour),
+        None => "Quiet hours turned off".to_string(),
+    };
+
+    match update_quiet_hours(chat_id, quiet_hours) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, confirmation).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update quiet hours: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update quiet hours").limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn shut_up(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let args = msg.text().map(command_args).unwrap_or_default().trim();
+    let seconds = if args.is_empty() {
+        DEFAULT_SHUTUP_SECONDS
+    } else {
+        match parse_duration(args) {
+            Some(seconds) => seconds,
+            None => {
+                bot.send_message(msg.chat.id, "Invalid format. Usage: /shutup 30m (or 2h, 1d, at most a year)").limited().await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let chat_id = msg.chat.id.0.to_string();
+
+    match update_silenced_until(chat_id, Some(Utc::now().timestamp() + seconds)) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, format!("Okay, staying quiet for {}. /wake to bring me back", format_duration(seconds))).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to silence chat: {}", e);
+            bot.send_message(msg.chat.id, "Failed to silence the bot").limited().await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn wake(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0.to_string();
+
+    match update_silenced_until(chat_id, None) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, "I'm back").limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to wake the bot: {}", e);
+            bot.send_message(msg.chat.id, "Failed to wake the bot").limited().await?;
+        }
+    }
+
+    Ok(())
+}
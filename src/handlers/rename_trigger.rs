@@ -0,0 +1,58 @@
+use teloxide::{prelude::*, types::MessageId};
+use crate::handlers::target::{command_args, parse_target};
+use crate::utils::dictionary::rename_trigger_key;
+use std::time::Duration;
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /rename @username old=>new (or /rename all old=>new)";
+
+pub async fn rename_trigger(bot: Bot, msg: Message) -> ResponseResult<()> {
+    if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+        println!("Failed to delete command message: {}", e);
+    }
+
+    if let Some(text) = msg.text() {
+        let Some((target, trigger)) = parse_target(&msg, command_args(text)) else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+
+        let Some((old_trigger, new_trigger)) = trigger.split_once("=>") else {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        };
+
+        let old_trigger = old_trigger.trim().to_string();
+        let new_trigger = new_trigger.trim().to_lowercase();
+        if old_trigger.is_empty() || new_trigger.is_empty() {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        }
+
+        let chat_id = msg.chat.id.0.to_string();
+
+        match rename_trigger_key(chat_id, target.as_ref(), old_trigger.clone(), new_trigger.clone()) {
+            Ok(_) => {
+                let success_msg = bot.send_message(
+                    msg.chat.id,
+                    format!("Renamed '{}' to '{}'!", old_trigger, new_trigger)
+                ).limited().await?;
+                if let Some(success_msg) = success_msg {
+                    tokio::spawn(delete_message_after_delay(bot.clone(), success_msg.chat.id, success_msg.id, 1));
+                }
+            }
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("Error renaming trigger: {}", e)).limited().await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn delete_message_after_delay(bot: Bot, chat_id: ChatId, message_id: MessageId, seconds: u64) {
+    tokio::time::sleep(Duration::from_secs(seconds)).await;
+    if let Err(e) = bot.delete_message(chat_id, message_id).await {
+        println!("Failed to delete message: {}", e);
+    }
+}
@@ -3,7 +3,9 @@ use std::env;
 use std::io::Cursor;
 use teloxide::net::Download;
 use teloxide::prelude::*;
-use crate::utils::dictionary::initialize_dictionary;
+use std::collections::HashMap;
+use crate::utils::dictionary::{initialize_dictionary, import_chat_dialogs, import_packs, Dialog, Pack};
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn set_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
     let file_path = if env::var("APP_ENV").unwrap() == "test" {
@@ -15,7 +17,7 @@ pub async fn set_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
     if let Some(document) = msg.document() {
         if let Some(file_name) = &document.file_name {
             if !file_name.ends_with(".json") {
-                bot.send_message(msg.chat.id, "Please upload a JSON file").await?;
+                bot.send_message(msg.chat.id, "Please upload a JSON file").limited().await?;
                 return Ok(());
             }
         }
@@ -30,10 +32,23 @@ pub async fn set_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
 
         match std::str::from_utf8(&file_content) {
             Ok(json_str) => {
-                if let Err(e) = serde_json::from_str::<serde_json::Value>(json_str) {
-                    log::error!("Invalid JSON format: {}", e);
-                    bot.send_message(msg.chat.id, "Invalid JSON format").await?;
-                    return Ok(());
+                let json_value = match serde_json::from_str::<serde_json::Value>(json_str) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        log::error!("Invalid JSON format: {}", e);
+                        bot.send_message(msg.chat.id, "Invalid JSON format").limited().await?;
+                        return Ok(());
+                    }
+                };
+
+                // A file with only "dialogs" or "packs" is merged instead of replacing the dictionary
+                if json_value.get("chats").is_none() {
+                    if let Some(dialogs) = json_value.get("dialogs") {
+                        return import_dialogs(bot, msg.chat.id, dialogs.clone()).await;
+                    }
+                    if let Some(packs) = json_value.get("packs") {
+                        return import_trigger_packs(bot, msg.chat.id, packs.clone()).await;
+                    }
                 }
 
                 match fs::write(file_path, json_str) {
@@ -44,22 +59,66 @@ pub async fn set_dict(bot: Bot, msg: Message) -> ResponseResult<()> {
                             log::error!("Failed to initialize dictionary: {}", e);
                         }
                         
-                        bot.send_message(msg.chat.id, "Dictionary file updated successfully!").await?;
+                        bot.send_message(msg.chat.id, "Dictionary file updated successfully!").limited().await?;
                     }
                     Err(e) => {
                         log::error!("Failed to write dictionary file: {}", e);
-                        bot.send_message(msg.chat.id, "Failed to update dictionary file").await?;
+                        bot.send_message(msg.chat.id, "Failed to update dictionary file").limited().await?;
                     }
                 }
             }
             Err(e) => {
                 log::error!("Failed to parse file content as UTF-8: {}", e);
-                bot.send_message(msg.chat.id, "File content is not valid UTF-8").await?;
+                bot.send_message(msg.chat.id, "File content is not valid UTF-8").limited().await?;
             }
         }
     } else {
-        bot.send_message(msg.chat.id, "Please attach a JSON file to update the dictionary").await?;
+        bot.send_message(msg.chat.id, "Please attach a JSON file to update the dictionary").limited().await?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn import_dialogs(bot: Bot, chat_id: ChatId, dialogs: serde_json::Value) -> ResponseResult<()> {
+    let dialogs: HashMap<String, Dialog> = match serde_json::from_value(dialogs) {
+        Ok(dialogs) => dialogs,
+        Err(e) => {
+            log::error!("Invalid dialogs format: {}", e);
+            bot.send_message(chat_id, format!("Invalid dialogs format: {}", e)).limited().await?;
+            return Ok(());
+        }
+    };
+
+    match import_chat_dialogs(chat_id.0.to_string(), dialogs) {
+        Ok(count) => {
+            bot.send_message(chat_id, format!("Imported {} dialogs", count)).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to import dialogs: {}", e);
+            bot.send_message(chat_id, "Failed to import dialogs").limited().await?;
+        }
+    }
+    Ok(())
+}
+
+async fn import_trigger_packs(bot: Bot, chat_id: ChatId, packs: serde_json::Value) -> ResponseResult<()> {
+    let packs: HashMap<String, Pack> = match serde_json::from_value(packs) {
+        Ok(packs) => packs,
+        Err(e) => {
+            log::error!("Invalid packs format: {}", e);
+            bot.send_message(chat_id, format!("Invalid packs format: {}", e)).limited().await?;
+            return Ok(());
+        }
+    };
+
+    match import_packs(packs) {
+        Ok(count) => {
+            bot.send_message(chat_id, format!("Imported {} trigger packs", count)).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to import packs: {}", e);
+            bot.send_message(chat_id, "Failed to import trigger packs").limited().await?;
+        }
+    }
+    Ok(())
+}
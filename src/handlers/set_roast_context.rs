@@ -0,0 +1,31 @@
+use teloxide::prelude::*;
+use crate::handlers::target::command_args;
+use crate::utils::dictionary::update_context_window;
+use crate::utils::history::MAX_HISTORY;
+use crate::utils::rate_limit::RateLimitExt;
+
+pub async fn set_roast_context(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let args = msg.text().map(command_args).unwrap_or_default().trim();
+    let Some(context_window) = args.parse::<usize>().ok().filter(|window| *window <= MAX_HISTORY) else {
+        bot.send_message(msg.chat.id, format!("Invalid format. Usage: /roastcontext 10 (0-{}, 0 turns it off)", MAX_HISTORY)).limited().await?;
+        return Ok(());
+    };
+
+    let chat_id = msg.chat.id.0.to_string();
+
+    match update_context_window(chat_id, context_window) {
+        Ok(_) => {
+            let confirmation = match context_window {
+                0 => "Roasts no longer see earlier messages".to_string(),
+                window => format!("Roasts now see the last {} messages", window),
+            };
+            bot.send_message(msg.chat.id, confirmation).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update roast context: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update roast context").limited().await?;
+        }
+    }
+
+    Ok(())
+}
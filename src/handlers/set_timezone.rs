@@ -0,0 +1,33 @@
+use teloxide::prelude::*;
+use crate::utils::dictionary::update_utc_offset;
+use crate::utils::time::{format_utc_offset, parse_utc_offset};
+use crate::utils::rate_limit::RateLimitExt;
+
+pub async fn set_timezone(bot: Bot, msg: Message) -> ResponseResult<()> {
+    if let Some(msg_text) = msg.text() {
+        let parts: Vec<&str> = msg_text.splitn(2, ' ').collect();
+        if parts.len() < 2 {
+            bot.send_message(msg.chat.id, "Invalid format. Usage: /settz +5").limited().await?;
+            return Ok(());
+        }
+
+        let Some(utc_offset) = parse_utc_offset(parts[1]) else {
+            bot.send_message(msg.chat.id, "Invalid timezone. Examples: +5, -3, +5:30").limited().await?;
+            return Ok(());
+        };
+
+        let chat_id = msg.chat.id.0.to_string();
+
+        match update_utc_offset(chat_id, utc_offset) {
+            Ok(_) => {
+                bot.send_message(msg.chat.id, format!("Timezone set to {}", format_utc_offset(utc_offset))).limited().await?;
+            }
+            Err(e) => {
+                log::error!("Failed to update timezone: {}", e);
+                bot.send_message(msg.chat.id, "Failed to update timezone").limited().await?;
+            }
+        }
+    }
+
+    Ok(())
+}
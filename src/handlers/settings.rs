@@ -0,0 +1,80 @@
+use teloxide::prelude::*;
+use crate::utils::dictionary::{get_chat, Chat, CounterMode, ReplyPolicy};
+use crate::utils::rate_limit::RateLimitExt;
+use crate::utils::time::format_utc_offset;
+use chrono::Utc;
+
+pub async fn settings(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let Some(chat) = get_chat(&msg.chat.id.0.to_string()) else {
+        bot.send_message(msg.chat.id, "No settings for this chat yet").limited().await?;
+        return Ok(());
+    };
+
+    let mut text = format!("Settings for {}:\n", chat.name);
+    text.push_str(&format!("Replies: {}\n", chat.reply_policy_label()));
+    text.push_str(&format!("Counter: {}\n", counter_label(&chat, chat.message_counter, chat.reply_frequency)));
+
+    let mut overrides: Vec<_> = chat.users
+        .iter()
+        .filter_map(|(user_key, user)| user.reply_frequency.map(|frq| (chat.user_label(user_key), frq, user.message_counter)))
+        .collect();
+    overrides.sort();
+    for (label, frequency, counter) in overrides {
+        text.push_str(&format!("  {}: every {}, {}\n", label, frequency, counter_label(&chat, counter, frequency)));
+    }
+
+    text.push_str(&format!("Roast level: {}\n", chat.roast_level));
+    text.push_str(&format!("Roast context: last {} messages\n", chat.context_window));
+    text.push_str(&format!("Timezone: {}\n", format_utc_offset(chat.utc_offset)));
+
+    match &chat.quiet_hours {
+        Some(window) => text.push_str(&format!("Quiet hours: {}:00-{}:00\n", window.start_hour, window.end_hour)),
+        None => text.push_str("Quiet hours: off\n"),
+    }
+    let silenced_until = chat.silenced_until
+        .filter(|until| *until > Utc::now().timestamp())
+        .and_then(|until| chat.local_time(until));
+    if let Some(until) = silenced_until {
+        text.push_str(&format!("Silenced until {}\n", until.format("%Y-%m-%d %H:%M")));
+    }
+
+    match (chat.watch_edits, &chat.edit_reply) {
+        (true, Some(reply)) => text.push_str(&format!("Edited messages: watched, replying '{}'\n", reply)),
+        (true, None) => text.push_str("Edited messages: watched\n"),
+        (false, _) => text.push_str("Edited messages: ignored\n"),
+    }
+
+    if chat.installed_packs.is_empty() {
+        text.push_str("Packs: none\n");
+    } else {
+        text.push_str(&format!("Packs: {}\n", chat.installed_packs.join(", ")));
+    }
+
+    bot.send_message(msg.chat.id, text).limited().await?;
+    Ok(())
+}
+
+// Where a reply counter stands and when it lets the next reply through
+fn counter_label(chat: &Chat, counter: u32, frequency: u32) -> String {
+    let frequency = frequency.max(1);
+    match chat.counter_mode {
+        CounterMode::Matched if chat.reply_policy == ReplyPolicy::EveryNth => {
+            let left = frequency - counter % frequency;
+            format!("{} matched messages counted, replies on the {} match from now", counter, ordinal(left))
+        }
+        CounterMode::Matched => format!("{} matched messages counted", counter),
+        CounterMode::All if counter >= frequency => format!("{} messages since the last reply, replies on the next match", counter),
+        CounterMode::All => format!("{} messages since the last reply, {} more before the next one", counter, frequency - counter),
+    }
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
@@ -1,9 +1,10 @@
 use teloxide::{prelude::*, types::Message};
+use crate::utils::rate_limit::RateLimitExt;
 
 pub async fn start(bot: Bot, msg: Message) -> ResponseResult<()> {
     bot.send_message(
         msg.chat.id,
         "Hello! I'm your Rust Telegram bot. Use /help to see available commands."
-    ).await?;
+    ).limited().await?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use teloxide::prelude::*;
+use teloxide::types::MessageEntityKind;
+use crate::utils::dictionary::UserTarget;
+
+// Who a trigger command applies to; `None` means the whole chat ("all")
+pub(crate) type Target = Option<UserTarget>;
+
+// Splits command arguments into the target and the rest.
+// "@user rest" and "all rest" name the target explicitly, otherwise a command sent
+// as a reply targets the replied message's author, and a bare first word is a username.
+pub(crate) fn parse_target<'a>(msg: &Message, args: &'a str) -> Option<(Target, &'a str)> {
+    let args = args.trim();
+    let (first, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+
+    if first == "all" {
+        return Some((None, rest.trim()));
+    }
+    if let Some(username) = first.strip_prefix('@').filter(|u| !u.is_empty()) {
+        return Some((Some(UserTarget::Username(username.to_string())), rest.trim()));
+    }
+    if let Some(user) = msg.reply_to_message().and_then(|m| m.from.as_ref()) {
+        return Some((Some(UserTarget::from(user)), args));
+    }
+    if !first.is_empty() && !first.contains('=') && !rest.trim().is_empty() {
+        return Some((Some(UserTarget::Username(first.to_string())), rest.trim()));
+    }
+    None
+}
+
+// Like parse_target, but only "@user rest" or a reply name a member, never "all" or a bare word
+pub(crate) fn parse_member_target<'a>(msg: &Message, args: &'a str) -> Option<(UserTarget, &'a str)> {
+    let args = args.trim();
+
+    if let Some((username, rest)) = explicit_username(args) {
+        return Some((username, rest));
+    }
+    msg.reply_to_message()
+        .and_then(|m| m.from.as_ref())
+        .map(|user| (UserTarget::from(user), args))
+}
+
+// "@user rest" only
+pub(crate) fn explicit_username(args: &str) -> Option<(UserTarget, &str)> {
+    let (first, rest) = args.trim().split_once(char::is_whitespace).unwrap_or((args.trim(), ""));
+    let username = first.strip_prefix('@').filter(|u| !u.is_empty())?;
+
+    Some((UserTarget::Username(username.to_string()), rest.trim()))
+}
+
+// Arguments following the command name
+pub(crate) fn command_args(text: &str) -> &str {
+    text.split_once(char::is_whitespace).map(|(_, args)| args).unwrap_or_default()
+}
+
+// "mention" or "mention:keyword" names a mention trigger, returning its keyword
+pub(crate) fn mention_keyword(trigger: &str) -> Option<&str> {
+    if trigger == "mention" {
+        return Some("");
+    }
+    trigger.strip_prefix("mention:").map(str::trim).filter(|keyword| !keyword.is_empty())
+}
+
+// Members a message points at: @mentions, text mentions and the author of the replied message
+pub(crate) fn mention_targets(msg: &Message) -> Vec<UserTarget> {
+    let entities = msg.parse_entities()
+        .or_else(|| msg.parse_caption_entities())
+        .unwrap_or_default();
+
+    let mut targets: Vec<UserTarget> = entities
+        .iter()
+        .filter_map(|entity| match entity.kind() {
+            MessageEntityKind::Mention => Some(UserTarget::Username(entity.text().trim_start_matches('@').to_string())),
+            MessageEntityKind::TextMention { user } => Some(UserTarget::from(user)),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(user) = msg.reply_to_message().and_then(|m| m.from.as_ref()) {
+        targets.push(UserTarget::from(user));
+    }
+    targets
+}
@@ -0,0 +1,65 @@
+use teloxide::prelude::*;
+use crate::utils::dictionary::{get_chat, Chat, TriggerRef, TriggerScope};
+use crate::utils::rate_limit::RateLimitExt;
+
+const TOP_TRIGGERS: usize = 10;
+const MAX_UNUSED_SHOWN: usize = 30;
+
+pub async fn trigger_stats(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0.to_string();
+
+    let Some(chat) = get_chat(&chat_id) else {
+        bot.send_message(msg.chat.id, "No triggers in this chat yet").limited().await?;
+        return Ok(());
+    };
+
+    let (mut used, unused): (Vec<_>, Vec<_>) = chat.triggers()
+        .into_iter()
+        .partition(|trigger| trigger.reply.stats.hits > 0);
+
+    if used.is_empty() && unused.is_empty() {
+        bot.send_message(msg.chat.id, "No triggers in this chat yet").limited().await?;
+        return Ok(());
+    }
+
+    used.sort_by_key(|trigger| std::cmp::Reverse(trigger.reply.stats.hits));
+
+    let mut report = String::from("Top triggers:\n");
+    if used.is_empty() {
+        report.push_str("none fired yet\n");
+    }
+    for (i, trigger) in used.iter().take(TOP_TRIGGERS).enumerate() {
+        let stats = &trigger.reply.stats;
+        report.push_str(&format!("{}. {} — {} hits", i + 1, trigger_label(&chat, trigger), stats.hits));
+
+        if let Some(last_fired) = stats.last_fired.and_then(|ts| chat.local_time(ts)) {
+            report.push_str(&format!(", last {}", last_fired.format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(user_key) = stats.top_firer() {
+            report.push_str(&format!(", mostly by {}", chat.user_label(user_key)));
+        }
+        report.push('\n');
+    }
+
+    report.push_str(&format!("\nNever used ({}):\n", unused.len()));
+    for trigger in unused.iter().take(MAX_UNUSED_SHOWN) {
+        report.push_str(&format!("{}\n", trigger_label(&chat, trigger)));
+    }
+    if unused.len() > MAX_UNUSED_SHOWN {
+        report.push_str(&format!("...and {} more\n", unused.len() - MAX_UNUSED_SHOWN));
+    }
+
+    bot.send_message(msg.chat.id, report).limited().await?;
+    Ok(())
+}
+
+fn trigger_label(chat: &Chat, trigger: &TriggerRef) -> String {
+    let owner = match &trigger.scope {
+        TriggerScope::User(user_key) => chat.user_label(user_key),
+        TriggerScope::Mention(user_key) => format!("mentions of {}", chat.user_label(user_key)),
+        TriggerScope::Common => "all".to_string(),
+        TriggerScope::Pack(name) => format!("pack {}", name),
+    };
+    let kind = if trigger.sticker { "sticker " } else { "" };
+    format!("{}'{}' ({})", kind, trigger.trigger, owner)
+}
@@ -0,0 +1,42 @@
+use teloxide::prelude::*;
+use crate::handlers::target::command_args;
+use crate::utils::dictionary::update_watch_edits;
+use crate::utils::rate_limit::RateLimitExt;
+
+const USAGE: &str = "Invalid format. Usage: /watchedits on [caught you editing reply] or /watchedits off";
+
+pub async fn watch_edits(bot: Bot, msg: Message) -> ResponseResult<()> {
+    let args = msg.text().map(command_args).unwrap_or_default().trim();
+    let (mode, edit_reply) = match args.split_once(char::is_whitespace) {
+        Some((mode, reply)) => (mode, Some(reply.trim().to_string())),
+        None => (args, None),
+    };
+
+    let watch = match mode.to_lowercase().as_str() {
+        "on" => true,
+        "off" if edit_reply.is_none() => false,
+        _ => {
+            bot.send_message(msg.chat.id, USAGE).limited().await?;
+            return Ok(());
+        }
+    };
+
+    let chat_id = msg.chat.id.0.to_string();
+    let confirmation = match (watch, &edit_reply) {
+        (true, Some(reply)) => format!("Watching edited messages, edits that fire a trigger get '{}'", reply),
+        (true, None) => "Watching edited messages for triggers".to_string(),
+        (false, _) => "Edited messages are ignored".to_string(),
+    };
+
+    match update_watch_edits(chat_id, watch, edit_reply) {
+        Ok(_) => {
+            bot.send_message(msg.chat.id, confirmation).limited().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to update edit watching: {}", e);
+            bot.send_message(msg.chat.id, "Failed to update edit watching").limited().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -9,19 +9,34 @@ use handlers::{
     init_users::*,
     add_trigger::*,
     delete_trigger::*,
+    edit_trigger::*,
+    rename_trigger::*,
+    copy_triggers::*,
+    packs::*,
     get_dict::*,
     set_dict::*,
     change_reply_frequency::*,
-    set_roast_level::*
+    set_roast_level::*,
+    set_roast_context::*,
+    profile::*,
+    set_timezone::*,
+    watch_edits::*,
+    quiet::*,
+    settings::*,
+    llm_stats::*,
+    trigger_stats::*,
+    list_triggers::*,
+    message::*
 };
 use dotenv::dotenv;
-use teloxide::sugar::request::RequestReplyExt;
 use teloxide::{
     prelude::*,
     dispatching::Dispatcher,
 };
-use utils::dictionary::{DICTIONARY, get_dictionary_response, initialize_dictionary, print_dictionary};
-use utils::deepseek::DeepSeekRoaster;
+use utils::dictionary::{initialize_dictionary, print_dictionary};
+use utils::roast::Roaster;
+use utils::scheduler::run_trigger_expiry;
+use utils::rate_limit::RateLimitExt;
 
 #[tokio::main]
 async fn main() {
@@ -35,13 +50,13 @@ async fn main() {
         return;
     }
 
-    let ai_roaster = match DeepSeekRoaster::new() {
+    let ai_roaster = match Roaster::new() {
         Ok(roaster) => {
-            log::info!("DeepSeek AI roaster initialized successfully!");
+            log::info!("Roaster initialized successfully with {}!", roaster.provider_name());
             Some(roaster)
         },
         Err(e) => {
-            log::warn!("DeepSeek AI roaster not available: {}. Using simple roasts as fallback.", e);
+            log::warn!("LLM roasts not available: {}. Using template roasts instead.", e);
             None
         }
     };
@@ -55,6 +70,8 @@ async fn main() {
         Err(e) => log::warn!("Failed to clear pending updates: {}", e),
     }
 
+    tokio::spawn(run_trigger_expiry(bot.clone()));
+
     // Handler for commands
     let command_handler = Update::filter_message()
         .filter_command::<Command>()
@@ -63,97 +80,56 @@ async fn main() {
                 Command::Help => help(bot, msg).await,
                 Command::Start => start(bot, msg).await,
                 Command::Ping => {
-                    bot.send_message(msg.chat.id, "Pong !").await?;
+                    bot.send_message(msg.chat.id, "Pong !").limited().await?;
                     Ok(())
                 },
                 Command::InitUsers => init_users(bot, msg).await,
                 Command::Add => add_trigger(bot, msg).await,
                 Command::Delete => delete_trigger(bot, msg).await,
+                Command::Edit => edit_trigger(bot, msg).await,
+                Command::Rename => rename_trigger(bot, msg).await,
+                Command::CopyTriggers => copy_triggers(bot, msg).await,
+                Command::Packs => list_packs(bot, msg).await,
+                Command::InstallPack => install_pack(bot, msg).await,
+                Command::UninstallPack => uninstall_pack(bot, msg).await,
                 Command::GetDict => get_dict(bot, msg).await,
                 Command::SetDict => set_dict(bot, msg).await,
                 Command::ChangeFrq => change_reply_frequency(bot, msg).await,
                 Command::SetRoastLvl => set_roast_level(bot, msg).await,
+                Command::RoastContext => set_roast_context(bot, msg).await,
+                Command::Profile => profile(bot, msg).await,
+                Command::SetTz => set_timezone(bot, msg).await,
+                Command::WatchEdits => watch_edits(bot, msg).await,
+                Command::QuietHours => quiet_hours(bot, msg).await,
+                Command::ShutUp => shut_up(bot, msg).await,
+                Command::Wake => wake(bot, msg).await,
+                Command::Settings => settings(bot, msg).await,
+                Command::LlmStats => llm_stats(bot, msg).await,
+                Command::TriggerStats => trigger_stats(bot, msg).await,
+                Command::List => list_triggers(bot, msg).await,
             }
         });
 
     // Handler for regular messages
     let message_handler = Update::filter_message()
         .branch(dptree::endpoint(move |bot: Bot, msg: Message| {
-            let ai_roaster = ai_roaster.clone();
-            async move {
-                if let Some(text) = msg.text() {
-                    if let Some(user) = msg.from() {
-                        let chat_id = msg.chat.id.0.to_string();
-                        let username = user.username.clone().unwrap_or_default();
-
-                        if let Some(response) = get_dictionary_response(chat_id.clone(), username.clone(), text.to_string()) {
-                            let should_reply = if let Ok(mut lock) = DICTIONARY.lock() {
-                                if let Some(manager) = lock.as_mut() {
-                                    let should_reply = manager.should_reply_to_message(&chat_id);
-                                    manager.save().ok();
-                                    should_reply
-                                } else {
-                                    return Ok(());
-                                }
-                            } else {
-                                return Ok(());
-                            };
-
-                            if should_reply {
-                                bot.send_message(msg.chat.id, response)
-                                    .reply_to(msg)
-                                    .await?;
-                            }
-
-                            return Ok(());
-                        }
+            handle_message(bot, msg, ai_roaster.clone())
+        }));
 
-                        // let roast_chance = 1.0; // 100% chance to roast
-                        // let roast_chance = 0.5; // 50% chance to roast
-                        // let roast_chance = 0.2; // 20% chance to roast
-                        let roast_chance = 0.15; // 15% chance to roast
-                        // let roast_chance = 0.1; // 10% chance to roast
-                        let chat_roast_level = if let Ok(lock) = DICTIONARY.lock() {
-                            if let Some(manager) = lock.as_ref() {
-                                manager.get_roast_level(chat_id.clone())
-                            } else {
-                                3u8
-                            }
-                        } else {
-                            3u8
-                        };
-                        
-                        if rand::random::<f32>() < roast_chance && text.len() > 50 {
-                            let roast = if let Some(roaster) = &ai_roaster {
-                                match roaster.generate_roast(text, &username, chat_roast_level).await {
-                                    Ok(ai_roast) => {
-                                        Some(ai_roast)
-                                    }
-                                    Err(e) => {
-                                        log::warn!("AI roast failed: {}", e);
-                                        None
-                                    }
-                                }
-                            } else {
-                                None
-                            };
+    // Handler for edited messages
+    let edited_message_handler = Update::filter_edited_message()
+        .endpoint(handle_edited_message);
 
-                            if let Some(roast_message) = roast {
-                                bot.send_message(msg.chat.id, roast_message)
-                                    .reply_to(msg)
-                                    .await?;
-                            }
-                        }
-                    }
-                }
-                Ok(())
-            }
-        }));
+    // Handler for inline keyboard buttons
+    let callback_handler = Update::filter_callback_query()
+        .endpoint(handle_list_callback);
 
-    // Combine both handlers
+    // Combine all handlers
     let handler = dptree::entry()
         .branch(command_handler)
-        .branch(message_handler);
+        .branch(message_handler)
+        .branch(edited_message_handler)
+        .branch(callback_handler);
 
     Dispatcher::builder(bot, handler)
         .enable_ctrlc_handler()
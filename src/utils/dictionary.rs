@@ -1,23 +1,443 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::sync::Mutex;
 use std::path::Path;
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
+use crate::utils::time::format_duration;
 
 type Username = String;
+// numeric Telegram user id, or "@username" while the id is not known yet
+type UserKey = String;
 type ChatId = String;
 type Trigger = String;
 type Reply = String;
+// "id:<file_unique_id>", "emoji:<emoji>" or "set:<set name>"
+type StickerKey = String;
+type DialogName = String;
+type PackName = String;
 
 pub(crate) fn default_reply_frequency() -> u32 { 3 }
 
 // roast level 1-5
 pub(crate) fn default_roast_level() -> u8 { 3 }
 
+// firing probability in percent, 1-100
+pub(crate) fn default_trigger_probability() -> u8 { 100 }
+
+// minutes a user has to answer the bot before a dialog is dropped
+pub(crate) fn default_dialog_timeout() -> u32 { 5 }
+
+// chat timezone as minutes east of UTC, Kazakhstan time by default
+pub(crate) fn default_utc_offset() -> i32 { 5 * 60 }
+
+pub(crate) fn default_context_window() -> usize { 10 }
+
+// Profile notes kept per user; adding more drops the oldest
+pub const MAX_PROFILE_NOTES: usize = 10;
+pub const MAX_PROFILE_NOTE_CHARS: usize = 200;
+
+// How many (message, trigger) pairs a chat remembers so edits only fire triggers they added
+const FIRED_HISTORY: usize = 500;
+
+// Kind of message content a trigger is matched against
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Text,
+    Caption,
+    Poll,
+    Forward,
+}
+
+impl ContentKind {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "text" => Some(ContentKind::Text),
+            "caption" => Some(ContentKind::Caption),
+            "poll" => Some(ContentKind::Poll),
+            "forward" => Some(ContentKind::Forward),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn default_content_kinds() -> Vec<ContentKind> {
+    vec![ContentKind::Text, ContentKind::Caption, ContentKind::Poll, ContentKind::Forward]
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveWindow {
+    // 0 = Monday .. 6 = Sunday, empty means every day
+    #[serde(default)]
+    pub days: Vec<u8>,
+
+    // local hours [start_hour, end_hour), wraps past midnight when start > end
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+impl ActiveWindow {
+    pub fn contains(&self, now: &DateTime<FixedOffset>) -> bool {
+        let hour = now.hour() as u8;
+        let in_hours = if self.start_hour <= self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        };
+        if !in_hours {
+            return false;
+        }
+
+        // After midnight a wrapping window still belongs to the day it started on
+        let started = if self.start_hour > self.end_hour && hour < self.end_hour {
+            now.weekday().pred()
+        } else {
+            now.weekday()
+        };
+        self.days.is_empty() || self.days.contains(&(started.num_days_from_monday() as u8))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TriggerStats {
+    pub hits: u32,
+
+    // unix timestamp of the last hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_fired: Option<i64>,
+
+    // hits per user key
+    #[serde(default)]
+    pub fired_by: HashMap<UserKey, u32>,
+}
+
+impl TriggerStats {
+    pub fn is_empty(&self) -> bool {
+        self.hits == 0
+    }
+
+    pub fn top_firer(&self) -> Option<&UserKey> {
+        self.fired_by.iter().max_by_key(|(_, hits)| **hits).map(|(user_key, _)| user_key)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TriggerReply {
+    pub reply: Reply,
+
+    #[serde(default = "default_trigger_probability")]
+    pub probability: u8,
+
+    // unix timestamp after which the trigger gets pruned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<ActiveWindow>,
+
+    #[serde(default = "default_content_kinds")]
+    pub content: Vec<ContentKind>,
+
+    #[serde(default, skip_serializing_if = "TriggerStats::is_empty")]
+    pub stats: TriggerStats,
+}
+
+impl TriggerReply {
+    pub fn new(reply: Reply) -> Self {
+        Self {
+            reply,
+            probability: default_trigger_probability(),
+            expires_at: None,
+            active: None,
+            content: default_content_kinds(),
+            stats: TriggerStats::default(),
+        }
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn applies_to(&self, kind: ContentKind) -> bool {
+        self.content.contains(&kind)
+    }
+
+    pub fn is_active(&self, now: &DateTime<FixedOffset>) -> bool {
+        !self.is_expired(now.timestamp())
+            && self.active.as_ref().is_none_or(|window| window.contains(now))
+    }
+
+    // Rolls the per-trigger probability; always fires at 100%
+    pub fn roll(&self) -> bool {
+        self.probability >= 100 || rand::random_range(0..100u8) < self.probability
+    }
+}
+
+// Older dictionaries store replies as plain strings, newer ones as objects with metadata
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredReply {
+    Plain(Reply),
+    Detailed(TriggerReply),
+}
+
+fn deserialize_replies<'de, D>(deserializer: D) -> Result<HashMap<Trigger, TriggerReply>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let stored: HashMap<Trigger, StoredReply> = HashMap::deserialize(deserializer)?;
+    Ok(stored.into_iter().map(|(trigger, reply)| {
+        let reply = match reply {
+            StoredReply::Plain(reply) => TriggerReply::new(reply),
+            StoredReply::Detailed(reply) => reply,
+        };
+        (trigger, reply)
+    }).collect())
+}
+
+// Scripted exchange: the opening trigger gets a reply, then each step has to be
+// answered by the same user replying to the bot's last message in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Dialog {
+    pub trigger: Trigger,
+    pub reply: Reply,
+
+    #[serde(default)]
+    pub steps: Vec<DialogStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DialogStep {
+    pub trigger: Trigger,
+    pub reply: Reply,
+
+    #[serde(default = "default_dialog_timeout")]
+    pub within_minutes: u32,
+}
+
+// Where a user currently is in a dialog
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DialogState {
+    pub dialog: DialogName,
+    // index of the step the user is expected to answer next
+    pub step: usize,
+    pub bot_message_id: i32,
+    pub expires_at: i64,
+}
+
+// Dialog reply to send, and the step to wait for afterwards if the dialog goes on
+#[derive(Debug, Clone)]
+pub struct DialogMatch {
+    pub dialog: DialogName,
+    pub reply: Reply,
+    pub next_step: Option<usize>,
+}
+
+// What to do when a copied trigger already exists in the destination chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictMode {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl ConflictMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "skip" => Some(ConflictMode::Skip),
+            "overwrite" => Some(ConflictMode::Overwrite),
+            "rename" => Some(ConflictMode::Rename),
+            _ => None,
+        }
+    }
+}
+
+// Where a trigger added in bulk goes
+#[derive(Debug, Clone)]
+pub enum EntryTarget {
+    Common,
+    User(UserTarget),
+    Mention(UserTarget),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CopyReport {
+    pub copied: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+// Which messages advance the reply counter
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterMode {
+    // every reply_frequency-th matched message gets a reply
+    #[default]
+    Matched,
+    // a match gets a reply once reply_frequency messages of any kind went by since the last one
+    All,
+}
+
+impl CounterMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "matched" => Some(CounterMode::Matched),
+            "all" => Some(CounterMode::All),
+            _ => None,
+        }
+    }
+
+    // Advances the counter for a matched message and says whether it's time to reply
+    fn fires(self, counter: &mut u32, frequency: u32) -> bool {
+        match self {
+            CounterMode::Matched => {
+                *counter += 1;
+                counter.is_multiple_of(frequency)
+            }
+            CounterMode::All if *counter >= frequency => {
+                *counter = 0;
+                true
+            }
+            CounterMode::All => false,
+        }
+    }
+}
+
+// How a chat decides whether a matched trigger gets a reply
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplyPolicy {
+    // every reply_frequency-th matched message
+    #[default]
+    EveryNth,
+    Random { percent: u8 },
+    MinInterval { seconds: i64 },
+    // up to `capacity` replies in a burst, one more allowed every `refill_seconds`
+    TokenBucket { capacity: u32, refill_seconds: i64 },
+}
+
+// Bookkeeping for the time based reply policies
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReplyBudget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reply_at: Option<i64>,
+
+    #[serde(default)]
+    pub tokens: u32,
+
+    #[serde(default)]
+    pub refilled_at: i64,
+}
+
+impl ReplyBudget {
+    fn take_token(&mut self, capacity: u32, refill_seconds: i64, now: i64) -> bool {
+        let refill_seconds = refill_seconds.max(1);
+        let refills = (now - self.refilled_at) / refill_seconds;
+        if refills > 0 {
+            self.tokens = self.tokens.saturating_add(refills.min(capacity as i64) as u32).min(capacity);
+            self.refilled_at = if self.tokens == capacity { now } else { self.refilled_at + refills * refill_seconds };
+        }
+
+        if self.tokens == 0 {
+            return false;
+        }
+        self.tokens -= 1;
+        true
+    }
+}
+
+// Whose dictionary a trigger lives in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerScope {
+    User(UserKey),
+    // fires for messages that mention or reply to the user
+    Mention(UserKey),
+    Common,
+    Pack(PackName),
+}
+
+// A trigger that matched a message
+#[derive(Debug, Clone)]
+pub struct TriggerHit {
+    pub scope: TriggerScope,
+    pub sticker: bool,
+    pub trigger: Trigger,
+    pub reply: Reply,
+}
+
+// Borrowed view of any trigger stored in a chat
+pub struct TriggerRef<'a> {
+    pub scope: TriggerScope,
+    pub sticker: bool,
+    pub trigger: &'a Trigger,
+    pub reply: &'a TriggerReply,
+}
+
+// Member a trigger belongs to, either seen in the chat or only known by username
+#[derive(Debug, Clone)]
+pub enum UserTarget {
+    Member {
+        id: u64,
+        username: Option<Username>,
+        fullname: String,
+    },
+    Username(Username),
+}
+
+impl From<&teloxide::types::User> for UserTarget {
+    fn from(user: &teloxide::types::User) -> Self {
+        UserTarget::Member {
+            id: user.id.0,
+            username: user.username.clone(),
+            fullname: user.full_name(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct User {
     pub fullname: String,
-    pub replies: HashMap<Trigger, Reply>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<Username>,
+
+    #[serde(deserialize_with = "deserialize_replies")]
+    pub replies: HashMap<Trigger, TriggerReply>,
+
+    #[serde(default, deserialize_with = "deserialize_replies")]
+    pub sticker_replies: HashMap<StickerKey, TriggerReply>,
+
+    // fire when someone mentions or replies to this user; an empty key matches any such message
+    #[serde(default, deserialize_with = "deserialize_replies")]
+    pub mention_replies: HashMap<Trigger, TriggerReply>,
+
+    // overrides the chat's reply frequency for this user, counted separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_frequency: Option<u32>,
+
+    #[serde(default)]
+    pub message_counter: u32,
+
+    // running jokes the roasts may bring up, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profile: Vec<String>,
+}
+
+impl User {
+    pub fn new(fullname: String) -> Self {
+        Self {
+            fullname,
+            username: None,
+            replies: HashMap::new(),
+            sticker_replies: HashMap::new(),
+            mention_replies: HashMap::new(),
+            reply_frequency: None,
+            message_counter: 0,
+            profile: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -27,286 +447,1556 @@ pub struct Chat {
     #[serde(default = "default_reply_frequency")]
     pub reply_frequency: u32,
 
+    #[serde(default)]
+    pub reply_policy: ReplyPolicy,
+
+    #[serde(default)]
+    pub reply_budget: ReplyBudget,
+
+    #[serde(default)]
+    pub counter_mode: CounterMode,
+
     #[serde(default = "default_roast_level")]
     pub roast_level: u8,
 
+    // how many recent messages roasts get to see, 0 for none
+    #[serde(default = "default_context_window")]
+    pub context_window: usize,
+
+    #[serde(default = "default_utc_offset")]
+    pub utc_offset: i32,
+
     pub name: String,
-    pub users: HashMap<Username, User>,
-    pub common_replies: HashMap<Trigger, Reply>,
+    pub users: HashMap<UserKey, User>,
+
+    #[serde(deserialize_with = "deserialize_replies")]
+    pub common_replies: HashMap<Trigger, TriggerReply>,
+
+    #[serde(default, deserialize_with = "deserialize_replies")]
+    pub sticker_replies: HashMap<StickerKey, TriggerReply>,
+
+    #[serde(default)]
+    pub dialogs: HashMap<DialogName, Dialog>,
+
+    #[serde(default)]
+    pub dialog_states: HashMap<UserKey, DialogState>,
+
+    // consulted in install order after the chat's own triggers
+    #[serde(default)]
+    pub installed_packs: Vec<PackName>,
+
+    // match triggers on edited messages too, optionally calling the edit out
+    #[serde(default)]
+    pub watch_edits: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_reply: Option<Reply>,
+
+    // no unsolicited replies or roasts during these local hours, or until the deadline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<ActiveWindow>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silenced_until: Option<i64>,
+
+    // triggers each message matched or fired, whether or not they replied
+    #[serde(skip)]
+    fired: VecDeque<(i32, TriggerScope, Trigger)>,
+}
+
+// Curated bundle of common triggers any chat can install
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Pack {
+    #[serde(default)]
+    pub description: String,
+
+    #[serde(deserialize_with = "deserialize_replies")]
+    pub replies: HashMap<Trigger, TriggerReply>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct DictionaryManager {
     pub chats: HashMap<ChatId, Chat>,
-}
 
-// Global instance as Option
-pub(crate) static DICTIONARY: Mutex<Option<DictionaryManager>> = Mutex::new(None);
+    #[serde(default)]
+    pub packs: HashMap<PackName, Pack>,
+}
 
-impl DictionaryManager {
-    pub fn should_reply_to_message(&mut self, chat_id: &ChatId) -> bool {
-        let chat = self.chats.entry(chat_id.clone()).or_insert_with(|| Chat {
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
+impl Chat {
+    pub fn new(name: String) -> Self {
+        Self {
             message_counter: 0,
             reply_frequency: default_reply_frequency(),
-            common_replies: HashMap::new(),
+            reply_policy: ReplyPolicy::default(),
+            reply_budget: ReplyBudget::default(),
+            counter_mode: CounterMode::default(),
             roast_level: default_roast_level(),
-        });
+            context_window: default_context_window(),
+            utc_offset: default_utc_offset(),
+            name,
+            users: HashMap::new(),
+            common_replies: HashMap::new(),
+            sticker_replies: HashMap::new(),
+            dialogs: HashMap::new(),
+            dialog_states: HashMap::new(),
+            installed_packs: Vec::new(),
+            watch_edits: false,
+            edit_reply: None,
+            quiet_hours: None,
+            silenced_until: None,
+            fired: VecDeque::new(),
+        }
+    }
 
-        if chat.reply_frequency == 0 {
-            chat.reply_frequency = default_reply_frequency();
+    pub fn has_fired(&self, message_id: i32, scope: &TriggerScope, trigger: &Trigger) -> bool {
+        self.fired
+            .iter()
+            .any(|(id, fired_scope, fired_trigger)| *id == message_id && fired_scope == scope && fired_trigger == trigger)
+    }
+
+    // Returns false if the trigger was already remembered for this message
+    fn remember_fired(&mut self, message_id: i32, scope: TriggerScope, trigger: Trigger) -> bool {
+        if self.has_fired(message_id, &scope, &trigger) {
+            return false;
         }
 
-        chat.message_counter += 1;
-        chat.message_counter % chat.reply_frequency == 0
+        if self.fired.len() >= FIRED_HISTORY {
+            self.fired.pop_front();
+        }
+        self.fired.push_back((message_id, scope, trigger));
+        true
     }
 
-    pub fn save(&self) -> Result<(), std::io::Error> {
-        let data = serde_json::to_string_pretty(&self).map_err(|e| {
-            std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
-        })?;
-        fs::write("dictionaries.json", data)
+    fn find_username_key(&self, username: &str) -> Option<UserKey> {
+        self.users
+            .iter()
+            .find(|(_, user)| user.username.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(username)))
+            .map(|(key, _)| key.clone())
     }
 
-    pub fn add_user_entry(&mut self, chat_id: ChatId, username: Username, trigger: String, reply: String) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
-        
-        if chat.reply_frequency == 0 {
-            chat.reply_frequency = default_reply_frequency()
+    // Finds the key a user is stored under, by id first and then by username
+    pub fn find_user_key(&self, target: &UserTarget) -> Option<UserKey> {
+        match target {
+            UserTarget::Member { id, username, .. } => {
+                let key = id.to_string();
+                if self.users.contains_key(&key) {
+                    return Some(key);
+                }
+                username.as_deref().and_then(|username| self.find_username_key(username))
+            }
+            UserTarget::Username(username) => self.find_username_key(username),
         }
+    }
 
-        let user = chat.users.entry(username).or_insert_with(|| User {
-            fullname: "New User".to_string(),
-            replies: HashMap::new(),
-        });
+    // Clears a username other members still hold from before it changed hands
+    fn release_username(&mut self, owner: &UserKey, username: &str) -> bool {
+        let mut released = false;
+        for (key, user) in self.users.iter_mut() {
+            let holds = user.username.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(username));
+            if holds && key != owner && !key.starts_with('@') {
+                user.username = None;
+                released = true;
+            }
+        }
+        released
+    }
 
-        user.replies.insert(trigger, reply);
+    // Gets or creates a user, moving an entry only known by username under the member's id
+    pub fn user_entry(&mut self, target: &UserTarget) -> &mut User {
+        match target {
+            UserTarget::Member { id, username, fullname } => {
+                let key = id.to_string();
+                if let Some(username) = username {
+                    self.release_username(&key, username);
+                }
+                if !self.users.contains_key(&key) {
+                    let user = username.as_deref()
+                        .and_then(|username| self.find_username_key(username))
+                        .filter(|pending| pending.starts_with('@'))
+                        .and_then(|pending| self.users.remove(&pending))
+                        .unwrap_or_else(|| User::new(fullname.clone()));
+                    self.users.insert(key.clone(), user);
+                }
+
+                let user = self.users.get_mut(&key).unwrap();
+                if username.is_some() {
+                    user.username = username.clone();
+                }
+                if user.fullname == "New User" {
+                    user.fullname = fullname.clone();
+                }
+                user
+            }
+            UserTarget::Username(username) => {
+                let key = self.find_username_key(username).unwrap_or_else(|| format!("@{}", username));
+                self.users.entry(key).or_insert_with(|| User {
+                    username: Some(username.clone()),
+                    ..User::new("New User".to_string())
+                })
+            }
+        }
     }
 
-    pub fn delete_user_entry(&mut self, chat_id: ChatId, username: Username, trigger: String) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
+    // Every trigger of the chat, user triggers first
+    pub fn triggers(&self) -> Vec<TriggerRef<'_>> {
+        let mut triggers = Vec::new();
 
-        if chat.reply_frequency == 0 {
-            chat.reply_frequency = default_reply_frequency()
+        for (user_key, user) in &self.users {
+            for (sticker, replies) in [(false, &user.replies), (true, &user.sticker_replies)] {
+                triggers.extend(replies.iter().map(|(trigger, reply)| TriggerRef {
+                    scope: TriggerScope::User(user_key.clone()),
+                    sticker,
+                    trigger,
+                    reply,
+                }));
+            }
+            triggers.extend(user.mention_replies.iter().map(|(trigger, reply)| TriggerRef {
+                scope: TriggerScope::Mention(user_key.clone()),
+                sticker: false,
+                trigger,
+                reply,
+            }));
+        }
+        for (sticker, replies) in [(false, &self.common_replies), (true, &self.sticker_replies)] {
+            triggers.extend(replies.iter().map(|(trigger, reply)| TriggerRef {
+                scope: TriggerScope::Common,
+                sticker,
+                trigger,
+                reply,
+            }));
+        }
+
+        triggers
+    }
+
+    // Pack triggers are shared between chats, so they have no per-chat entry
+    fn trigger_mut(&mut self, scope: &TriggerScope, sticker: bool, trigger: &Trigger) -> Option<&mut TriggerReply> {
+        let replies = match (scope, sticker) {
+            (TriggerScope::Pack(_), _) => return None,
+            (TriggerScope::Mention(user_key), _) => &mut self.users.get_mut(user_key)?.mention_replies,
+            (TriggerScope::User(user_key), false) => &mut self.users.get_mut(user_key)?.replies,
+            (TriggerScope::User(user_key), true) => &mut self.users.get_mut(user_key)?.sticker_replies,
+            (TriggerScope::Common, false) => &mut self.common_replies,
+            (TriggerScope::Common, true) => &mut self.sticker_replies,
+        };
+        replies.get_mut(trigger)
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        let silenced = self.silenced_until.is_some_and(|until| until > Utc::now().timestamp());
+        silenced || self.quiet_hours.as_ref().is_some_and(|window| window.contains(&self.local_now()))
+    }
+
+    pub fn reply_policy_label(&self) -> String {
+        match &self.reply_policy {
+            ReplyPolicy::EveryNth => format!("every {} matched messages", self.reply_frequency),
+            ReplyPolicy::Random { percent } => format!("{}% of matched messages", percent),
+            ReplyPolicy::MinInterval { seconds } => format!("at most once every {}", format_duration(*seconds)),
+            ReplyPolicy::TokenBucket { capacity, refill_seconds } => {
+                format!("bursts of up to {}, one more every {}", capacity, format_duration(*refill_seconds))
+            }
+        }
+    }
+
+    // Display name of a user key: @username, full name, or the key itself
+    pub fn user_label(&self, user_key: &UserKey) -> String {
+        match self.users.get(user_key) {
+            Some(User { username: Some(username), .. }) => format!("@{}", username),
+            Some(user) => user.fullname.clone(),
+            None => user_key.clone(),
+        }
+    }
+
+    fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap())
+    }
+
+    // Current time in the chat's timezone
+    pub fn local_now(&self) -> DateTime<FixedOffset> {
+        Utc::now().with_timezone(&self.offset())
+    }
+
+    // Unix timestamp in the chat's timezone
+    pub fn local_time(&self, timestamp: i64) -> Option<DateTime<FixedOffset>> {
+        DateTime::from_timestamp(timestamp, 0).map(|time| time.with_timezone(&self.offset()))
+    }
+}
+
+fn not_found(what: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, what)
+}
+
+// Stored trigger keys are lowercase, but older entries may not be
+fn find_trigger_key(replies: &HashMap<Trigger, TriggerReply>, trigger: &str) -> Result<Trigger, std::io::Error> {
+    let lowercase_trigger = trigger.to_lowercase();
+
+    if replies.contains_key(trigger) {
+        Ok(trigger.to_string())
+    } else if replies.contains_key(&lowercase_trigger) {
+        Ok(lowercase_trigger)
+    } else {
+        Err(not_found(&format!("Trigger '{}' not found", trigger)))
+    }
+}
+
+fn copy_replies(
+    source: &HashMap<Trigger, TriggerReply>,
+    destination: &mut HashMap<Trigger, TriggerReply>,
+    is_selected: &dyn Fn(&Trigger) -> bool,
+    mode: ConflictMode,
+    report: &mut CopyReport,
+) {
+    for (trigger, reply) in source.iter().filter(|(trigger, _)| is_selected(trigger)) {
+        // Statistics belong to the source chat
+        let reply = TriggerReply {
+            stats: TriggerStats::default(),
+            ..reply.clone()
+        };
+
+        if !destination.contains_key(trigger) {
+            destination.insert(trigger.clone(), reply);
+            report.copied += 1;
+            continue;
+        }
+
+        match mode {
+            ConflictMode::Skip => report.skipped += 1,
+            ConflictMode::Overwrite => {
+                destination.insert(trigger.clone(), reply);
+                report.overwritten += 1;
+            }
+            ConflictMode::Rename => {
+                let renamed = (2..)
+                    .map(|n| format!("{}_{}", trigger, n))
+                    .find(|candidate| !destination.contains_key(candidate))
+                    .unwrap();
+                destination.insert(renamed, reply);
+                report.renamed += 1;
+            }
+        }
+    }
+}
+
+// Global instance as Option
+pub(crate) static DICTIONARY: Mutex<Option<DictionaryManager>> = Mutex::new(None);
+
+impl DictionaryManager {
+    fn chat_entry(&mut self, chat_id: ChatId) -> &mut Chat {
+        self.chats.entry(chat_id).or_insert_with(|| Chat::new("New Chat".to_string()))
+    }
+
+    // Users with their own reply frequency advance their own counter, everyone else goes through the chat's policy
+    pub fn should_reply_to_message(&mut self, chat_id: &ChatId, user_id: u64) -> bool {
+        let chat = self.chat_entry(chat_id.clone());
+        let counter_mode = chat.counter_mode;
+
+        if let Some(user) = chat.users.get_mut(&user_id.to_string()) {
+            if let Some(reply_frequency) = user.reply_frequency.filter(|frq| *frq > 0) {
+                return counter_mode.fires(&mut user.message_counter, reply_frequency);
+            }
         }
 
-        let user = chat.users.entry(username).or_insert_with(|| User {
-            fullname: "New User".to_string(),
-            replies: HashMap::new(),
-        });
+        if chat.reply_frequency == 0 {
+            chat.reply_frequency = default_reply_frequency();
+        }
+
+        let now = Utc::now().timestamp();
+        let should_reply = match chat.reply_policy {
+            ReplyPolicy::EveryNth => counter_mode.fires(&mut chat.message_counter, chat.reply_frequency),
+            ReplyPolicy::Random { percent } => rand::random_range(0..100u8) < percent,
+            ReplyPolicy::MinInterval { seconds } => {
+                chat.reply_budget.last_reply_at.is_none_or(|last| now - last >= seconds)
+            }
+            ReplyPolicy::TokenBucket { capacity, refill_seconds } => {
+                chat.reply_budget.take_token(capacity, refill_seconds, now)
+            }
+        };
+
+        if should_reply {
+            chat.reply_budget.last_reply_at = Some(now);
+        }
+        should_reply
+    }
+
+    // In CounterMode::All every message advances the counters, matched or not
+    pub fn count_message(&mut self, chat_id: &ChatId, user_id: u64) {
+        let Some(chat) = self.chats.get_mut(chat_id).filter(|chat| chat.counter_mode == CounterMode::All) else {
+            return;
+        };
+
+        match chat.users.get_mut(&user_id.to_string()).filter(|user| user.reply_frequency.is_some()) {
+            Some(user) => user.message_counter = user.message_counter.saturating_add(1),
+            None => chat.message_counter = chat.message_counter.saturating_add(1),
+        }
+    }
+
+    // Older dictionaries key users by username; keep them as "@username" until their id is seen
+    pub fn migrate_user_keys(&mut self) -> bool {
+        let mut migrated = false;
+
+        for chat in self.chats.values_mut() {
+            let legacy_keys: Vec<UserKey> = chat.users
+                .keys()
+                .filter(|key| key.parse::<u64>().is_err() && !key.starts_with('@'))
+                .cloned()
+                .collect();
+
+            for key in legacy_keys {
+                if let Some(mut user) = chat.users.remove(&key) {
+                    user.username = Some(key.clone());
+                    chat.users.insert(format!("@{}", key), user);
+                    migrated = true;
+                }
+            }
+        }
+
+        migrated
+    }
+
+    // Keeps a known member keyed by id and their username current, returns whether anything changed
+    pub fn resolve_user(&mut self, chat_id: &ChatId, target: &UserTarget) -> bool {
+        let Some(chat) = self.chats.get_mut(chat_id) else {
+            return false;
+        };
+        let UserTarget::Member { id, username, .. } = target else {
+            return false;
+        };
+
+        let released = username.as_deref().is_some_and(|username| chat.release_username(&id.to_string(), username));
+        let changed = match chat.users.get(&id.to_string()) {
+            Some(user) => user.username != *username,
+            None => username.as_deref()
+                .and_then(|username| chat.find_username_key(username))
+                .is_some_and(|key| key.starts_with('@')),
+        };
+
+        if changed {
+            chat.user_entry(target);
+        }
+        changed || released
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let data = serde_json::to_string_pretty(&self).map_err(|e| {
+            std::io::Error::other(e.to_string())
+        })?;
+        fs::write("dictionaries.json", data)
+    }
+
+    pub fn add_user_entry(&mut self, chat_id: ChatId, target: &UserTarget, trigger: String, reply: TriggerReply) {
+        let chat = self.chat_entry(chat_id);
+        
+        if chat.reply_frequency == 0 {
+            chat.reply_frequency = default_reply_frequency()
+        }
+
+        let user = chat.user_entry(target);
+
+        user.replies.insert(trigger, reply);
+    }
+
+    pub fn delete_user_entry(&mut self, chat_id: ChatId, target: &UserTarget, trigger: String) -> Result<(), std::io::Error> {
+        let replies = self.replies_mut(&chat_id, Some(target))?;
+        let key = find_trigger_key(replies, &trigger)?;
+
+        replies.remove(&key);
+        Ok(())
+    }
+
+    pub fn add_common_entry(&mut self, chat_id: ChatId, trigger: String, reply: TriggerReply) {
+        let chat = self.chat_entry(chat_id);
+
+        if chat.reply_frequency == 0 {
+            chat.reply_frequency = default_reply_frequency()
+        }
+        
+        chat.common_replies.insert(trigger, reply);
+    }
+
+    pub fn delete_common_entry(&mut self, chat_id: ChatId, trigger: String) -> Result<(), std::io::Error> {
+        let replies = self.replies_mut(&chat_id, None)?;
+        let key = find_trigger_key(replies, &trigger)?;
+
+        replies.remove(&key);
+        Ok(())
+    }
+
+    // Text triggers of a user, or the chat's common ones when no user is given
+    fn replies_mut(&mut self, chat_id: &ChatId, target: Option<&UserTarget>) -> Result<&mut HashMap<Trigger, TriggerReply>, std::io::Error> {
+        let chat = self.chats.get_mut(chat_id).ok_or_else(|| not_found("Chat not found"))?;
+
+        match target {
+            Some(target) => {
+                let user_key = chat.find_user_key(target).ok_or_else(|| not_found("User not found"))?;
+                Ok(&mut chat.users.get_mut(&user_key).unwrap().replies)
+            }
+            None => Ok(&mut chat.common_replies),
+        }
+    }
+
+    // Changes the reply text, keeping the trigger's options and statistics
+    pub fn edit_entry(&mut self, chat_id: ChatId, target: Option<&UserTarget>, trigger: String, reply: Reply) -> Result<(), std::io::Error> {
+        let replies = self.replies_mut(&chat_id, target)?;
+        let key = find_trigger_key(replies, &trigger)?;
+
+        replies.get_mut(&key).unwrap().reply = reply;
+        Ok(())
+    }
+
+    // Moves a trigger to a new key, keeping its reply, options and statistics
+    pub fn rename_entry(&mut self, chat_id: ChatId, target: Option<&UserTarget>, trigger: String, new_trigger: String) -> Result<(), std::io::Error> {
+        let replies = self.replies_mut(&chat_id, target)?;
+        let key = find_trigger_key(replies, &trigger)?;
+
+        if replies.contains_key(&new_trigger) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Trigger '{}' already exists", new_trigger),
+            ));
+        }
+
+        let reply = replies.remove(&key).unwrap();
+        replies.insert(new_trigger, reply);
+        Ok(())
+    }
+
+    pub fn add_user_sticker_entry(&mut self, chat_id: ChatId, target: &UserTarget, key: StickerKey, reply: TriggerReply) {
+        let chat = self.chat_entry(chat_id);
+
+        let user = chat.user_entry(target);
+
+        user.sticker_replies.insert(key, reply);
+    }
+
+    pub fn delete_user_sticker_entry(&mut self, chat_id: ChatId, target: &UserTarget, key: StickerKey) -> Result<(), std::io::Error> {
+        let chat = self.chats.get_mut(&chat_id).ok_or_else(|| not_found("Chat not found"))?;
+        let user_key = chat.find_user_key(target).ok_or_else(|| not_found("User not found"))?;
+
+        chat.users.get_mut(&user_key).unwrap()
+            .sticker_replies
+            .remove(&key)
+            .map(|_| ())
+            .ok_or_else(|| not_found(&format!("Sticker '{}' not found", key)))
+    }
+
+    pub fn add_user_mention_entry(&mut self, chat_id: ChatId, target: &UserTarget, keyword: Trigger, reply: TriggerReply) {
+        let chat = self.chat_entry(chat_id);
+
+        let user = chat.user_entry(target);
+
+        user.mention_replies.insert(keyword, reply);
+    }
+
+    pub fn delete_user_mention_entry(&mut self, chat_id: ChatId, target: &UserTarget, keyword: Trigger) -> Result<(), std::io::Error> {
+        let chat = self.chats.get_mut(&chat_id).ok_or_else(|| not_found("Chat not found"))?;
+        let user_key = chat.find_user_key(target).ok_or_else(|| not_found("User not found"))?;
+        let replies = &mut chat.users.get_mut(&user_key).unwrap().mention_replies;
+
+        let key = find_trigger_key(replies, &keyword)?;
+        replies.remove(&key);
+        Ok(())
+    }
+
+    pub fn add_common_sticker_entry(&mut self, chat_id: ChatId, key: StickerKey, reply: TriggerReply) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.sticker_replies.insert(key, reply);
+    }
+
+    pub fn delete_common_sticker_entry(&mut self, chat_id: ChatId, key: StickerKey) -> Result<(), std::io::Error> {
+        let chat = self.chats.get_mut(&chat_id).ok_or_else(|| not_found("Chat not found"))?;
+
+        chat.sticker_replies
+            .remove(&key)
+            .map(|_| ())
+            .ok_or_else(|| not_found(&format!("Sticker '{}' not found", key)))
+    }
+
+    pub fn update_reply_freq(&mut self, chat_id: ChatId, reply_frq: u32) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.reply_frequency = reply_frq;
+        chat.reply_policy = ReplyPolicy::EveryNth;
+    }
+
+    pub fn update_counter_mode(&mut self, chat_id: ChatId, counter_mode: CounterMode) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.counter_mode = counter_mode;
+        chat.message_counter = 0;
+        for user in chat.users.values_mut() {
+            user.message_counter = 0;
+        }
+    }
+
+    pub fn update_reply_policy(&mut self, chat_id: ChatId, reply_policy: ReplyPolicy) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.reply_policy = reply_policy;
+        chat.reply_budget = ReplyBudget::default();
+    }
+
+    // `None` drops the override so the user falls back to the chat's frequency
+    pub fn update_user_reply_freq(&mut self, chat_id: ChatId, target: &UserTarget, reply_frq: Option<u32>) -> Result<(), std::io::Error> {
+        let chat = self.chats.get_mut(&chat_id).ok_or_else(|| not_found("Chat not found"))?;
+        let user_key = chat.find_user_key(target).ok_or_else(|| not_found("User not found"))?;
+
+        let user = chat.users.get_mut(&user_key).unwrap();
+        user.reply_frequency = reply_frq;
+        user.message_counter = 0;
+        Ok(())
+    }
+
+    // Members seen in the chat get an entry, a bare username has to be known already
+    pub fn add_profile_note(&mut self, chat_id: ChatId, target: &UserTarget, note: String) -> Result<(), std::io::Error> {
+        let chat = self.chat_entry(chat_id);
+
+        let user = match target {
+            UserTarget::Member { .. } => chat.user_entry(target),
+            UserTarget::Username(_) => {
+                let user_key = chat.find_user_key(target).ok_or_else(|| not_found("User not found"))?;
+                chat.users.get_mut(&user_key).unwrap()
+            }
+        };
+
+        let profile = &mut user.profile;
+        profile.push(note);
+        if profile.len() > MAX_PROFILE_NOTES {
+            profile.remove(0);
+        }
+        Ok(())
+    }
+
+    // Returns false if the user had nothing to clear
+    pub fn clear_profile(&mut self, chat_id: &ChatId, target: &UserTarget) -> bool {
+        let Some(chat) = self.chats.get_mut(chat_id) else {
+            return false;
+        };
+        let Some(user) = chat.find_user_key(target).and_then(|key| chat.users.get_mut(&key)) else {
+            return false;
+        };
+
+        !std::mem::take(&mut user.profile).is_empty()
+    }
+
+    pub fn get_profile(&self, chat_id: &ChatId, target: &UserTarget) -> Vec<String> {
+        self.chats.get(chat_id)
+            .and_then(|chat| chat.find_user_key(target).and_then(|key| chat.users.get(&key)))
+            .map(|user| user.profile.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update_roast_level(&mut self, chat_id: ChatId, roast_level: u8) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.roast_level = roast_level;
+    }
+
+    pub fn update_context_window(&mut self, chat_id: ChatId, context_window: usize) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.context_window = context_window;
+    }
+
+    pub fn update_utc_offset(&mut self, chat_id: ChatId, utc_offset: i32) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.utc_offset = utc_offset;
+    }
+
+    pub fn update_quiet_hours(&mut self, chat_id: ChatId, quiet_hours: Option<ActiveWindow>) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.quiet_hours = quiet_hours;
+    }
+
+    pub fn update_silenced_until(&mut self, chat_id: ChatId, silenced_until: Option<i64>) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.silenced_until = silenced_until;
+    }
+
+    pub fn update_watch_edits(&mut self, chat_id: ChatId, watch_edits: bool, edit_reply: Option<Reply>) {
+        let chat = self.chat_entry(chat_id);
+
+        chat.watch_edits = watch_edits;
+        chat.edit_reply = edit_reply;
+    }
+
+    // Removes expired triggers, returning the removed ones per chat
+    pub fn prune_expired(&mut self, now: i64) -> Vec<(ChatId, Vec<Trigger>)> {
+        let mut pruned = Vec::new();
+
+        for (chat_id, chat) in self.chats.iter_mut() {
+            let mut removed = Vec::new();
+            let mut prune = |trigger: &Trigger, reply: &TriggerReply| {
+                if reply.is_expired(now) {
+                    removed.push(trigger.clone());
+                    false
+                } else {
+                    true
+                }
+            };
+
+            chat.common_replies.retain(|trigger, reply| prune(trigger, reply));
+            chat.sticker_replies.retain(|trigger, reply| prune(trigger, reply));
+            for user in chat.users.values_mut() {
+                user.replies.retain(|trigger, reply| prune(trigger, reply));
+                user.sticker_replies.retain(|trigger, reply| prune(trigger, reply));
+                user.mention_replies.retain(|trigger, reply| prune(trigger, reply));
+            }
+
+            if !removed.is_empty() {
+                pruned.push((chat_id.clone(), removed));
+            }
+        }
+
+        pruned
+    }
+
+    // Continues the user's dialog when they reply to the bot's last dialog message,
+    // otherwise starts a dialog whose opening trigger the text contains
+    pub fn match_dialog(&mut self, chat_id: &ChatId, user_id: u64, replied_message_id: Option<i32>, text: &str) -> Option<DialogMatch> {
+        let chat = self.chats.get_mut(chat_id)?;
+        let user_key = user_id.to_string();
+        let lowercase_input = text.to_lowercase();
+        let now = Utc::now().timestamp();
+
+        if let Some(state) = chat.dialog_states.get(&user_key).cloned() {
+            let step = chat.dialogs.get(&state.dialog).and_then(|dialog| dialog.steps.get(state.step));
+
+            match step {
+                Some(step) if state.expires_at > now => {
+                    if replied_message_id == Some(state.bot_message_id)
+                        && lowercase_input.contains(&step.trigger.to_lowercase())
+                    {
+                        chat.dialog_states.remove(&user_key);
+                        let steps = chat.dialogs[&state.dialog].steps.len();
+                        return Some(DialogMatch {
+                            dialog: state.dialog,
+                            reply: step.reply.clone(),
+                            next_step: Some(state.step + 1).filter(|next| *next < steps),
+                        });
+                    }
+                }
+                _ => {
+                    chat.dialog_states.remove(&user_key);
+                }
+            }
+        }
+
+        chat.dialogs
+            .iter()
+            .find(|(_, dialog)| lowercase_input.contains(&dialog.trigger.to_lowercase()))
+            .map(|(name, dialog)| DialogMatch {
+                dialog: name.clone(),
+                reply: dialog.reply.clone(),
+                next_step: Some(0).filter(|_| !dialog.steps.is_empty()),
+            })
+    }
+
+    // Waits for the user to answer the given dialog step in reply to the bot's message
+    pub fn continue_dialog(&mut self, chat_id: &ChatId, user_id: u64, dialog: DialogName, step: usize, bot_message_id: i32) {
+        let Some(chat) = self.chats.get_mut(chat_id) else {
+            return;
+        };
+        let Some(within_minutes) = chat.dialogs.get(&dialog).and_then(|d| d.steps.get(step)).map(|s| s.within_minutes) else {
+            return;
+        };
+
+        chat.dialog_states.insert(user_id.to_string(), DialogState {
+            dialog,
+            step,
+            bot_message_id,
+            expires_at: Utc::now().timestamp() + within_minutes as i64 * 60,
+        });
+    }
+
+    pub fn import_dialogs(&mut self, chat_id: ChatId, dialogs: HashMap<DialogName, Dialog>) -> usize {
+        let chat = self.chat_entry(chat_id);
+        let count = dialogs.len();
+
+        chat.dialog_states.retain(|_, state| !dialogs.contains_key(&state.dialog));
+        chat.dialogs.extend(dialogs);
+        count
+    }
+
+    // Copies common and user text triggers, all or only the selected ones, into another chat.
+    // Users are keyed by Telegram id, so user triggers land on the same member in the destination.
+    pub fn copy_triggers(&mut self, from_chat_id: &ChatId, to_chat_id: ChatId, selected: &[Trigger], mode: ConflictMode) -> Result<CopyReport, std::io::Error> {
+        let source = self.chats.get(from_chat_id).cloned().ok_or_else(|| not_found("Source chat not found"))?;
+        let is_selected = |trigger: &Trigger| selected.is_empty() || selected.iter().any(|s| s.eq_ignore_ascii_case(trigger));
+        let mut report = CopyReport::default();
+
+        let destination = self.chat_entry(to_chat_id);
+        copy_replies(&source.common_replies, &mut destination.common_replies, &is_selected, mode, &mut report);
+
+        for (user_key, user) in &source.users {
+            if let Some(destination_user) = destination.users.get_mut(user_key) {
+                copy_replies(&user.replies, &mut destination_user.replies, &is_selected, mode, &mut report);
+                continue;
+            }
+
+            // Members are only added to the destination when they get at least one trigger
+            let mut replies = HashMap::new();
+            copy_replies(&user.replies, &mut replies, &is_selected, mode, &mut report);
+            if !replies.is_empty() {
+                destination.users.insert(user_key.clone(), User {
+                    username: user.username.clone(),
+                    replies,
+                    ..User::new(user.fullname.clone())
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub fn install_pack(&mut self, chat_id: ChatId, name: &str) -> Result<(), std::io::Error> {
+        if !self.packs.contains_key(name) {
+            return Err(not_found(&format!("Pack '{}' not found", name)));
+        }
+
+        let chat = self.chat_entry(chat_id);
+        if chat.installed_packs.iter().any(|installed| installed == name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Pack '{}' is already installed", name),
+            ));
+        }
+
+        chat.installed_packs.push(name.to_string());
+        Ok(())
+    }
+
+    pub fn uninstall_pack(&mut self, chat_id: ChatId, name: &str) -> Result<(), std::io::Error> {
+        let chat = self.chats.get_mut(&chat_id).ok_or_else(|| not_found("Chat not found"))?;
+        let position = chat.installed_packs
+            .iter()
+            .position(|installed| installed == name)
+            .ok_or_else(|| not_found(&format!("Pack '{}' is not installed", name)))?;
+
+        chat.installed_packs.remove(position);
+        Ok(())
+    }
+
+    pub fn get_response(&self, chat_id: ChatId, user_id: u64, key: String, kind: ContentKind) -> Option<TriggerHit> {
+        self.get_response_excluding(chat_id, user_id, key, kind, |_, _| false)
+    }
+
+    // Like get_response, passing over the triggers `skip` returns true for
+    pub fn get_response_excluding(
+        &self,
+        chat_id: ChatId,
+        user_id: u64,
+        key: String,
+        kind: ContentKind,
+        skip: impl Fn(&TriggerScope, &Trigger) -> bool,
+    ) -> Option<TriggerHit> {
+        let chat = self.chats.get(&chat_id)?;
+        let user_key = user_id.to_string();
+
+        let lowercase_input = key.to_lowercase();
+        let now = chat.local_now();
+        let fires = |scope: &TriggerScope, (k, v): &(&Trigger, &TriggerReply)| {
+            lowercase_input.contains(&k.to_lowercase())
+                && v.applies_to(kind) && v.is_active(&now) && !skip(scope, k) && v.roll()
+        };
+
+        // Members without personal triggers still get common and pack replies
+        let user_reply = chat.users.get(&user_key).and_then(|user| {
+            let scope = TriggerScope::User(user_key);
+            let (k, v) = user.replies.iter().find(|entry| fires(&scope, entry))?;
+            Some((scope, k, v))
+        });
+
+        user_reply
+            .or_else(|| {
+                chat.common_replies
+                    .iter()
+                    .find(|entry| fires(&TriggerScope::Common, entry))
+                    .map(|(k, v)| (TriggerScope::Common, k, v))
+            })
+            .or_else(|| {
+                chat.installed_packs.iter().find_map(|name| {
+                    let scope = TriggerScope::Pack(name.clone());
+                    let (k, v) = self.packs.get(name)?.replies.iter().find(|entry| fires(&scope, entry))?;
+                    Some((scope, k, v))
+                })
+            })
+            .map(|(scope, trigger, reply)| TriggerHit {
+                scope,
+                sticker: false,
+                trigger: trigger.clone(),
+                reply: reply.reply.clone(),
+            })
+    }
+
+    // Mention triggers of the members a message points at, never of the sender themselves
+    pub fn get_mention_response(&self, chat_id: ChatId, user_id: u64, targets: &[UserTarget], key: String, kind: ContentKind) -> Option<TriggerHit> {
+        let chat = self.chats.get(&chat_id)?;
+        let sender_key = user_id.to_string();
+
+        let lowercase_input = key.to_lowercase();
+        let now = chat.local_now();
+        let fires = |(k, v): &(&Trigger, &TriggerReply)| {
+            (k.is_empty() || lowercase_input.contains(&k.to_lowercase()))
+                && v.applies_to(kind) && v.is_active(&now) && v.roll()
+        };
+
+        targets.iter()
+            .filter_map(|target| chat.find_user_key(target))
+            .filter(|user_key| *user_key != sender_key)
+            .find_map(|user_key| {
+                let (trigger, reply) = chat.users.get(&user_key)?.mention_replies.iter().find(fires)?;
+                Some(TriggerHit {
+                    scope: TriggerScope::Mention(user_key),
+                    sticker: false,
+                    trigger: trigger.clone(),
+                    reply: reply.reply.clone(),
+                })
+            })
+    }
+
+    // Keys are tried in order, so pass the most specific one (sticker id) first
+    pub fn get_sticker_response(&self, chat_id: ChatId, user_id: u64, keys: &[StickerKey]) -> Option<TriggerHit> {
+        let chat = self.chats.get(&chat_id)?;
+        let user_key = user_id.to_string();
+        let now = chat.local_now();
+        let fires = |(_, reply): &(&StickerKey, &TriggerReply)| reply.is_active(&now) && reply.roll();
+
+        let user_reply = chat.users.get(&user_key).and_then(|user| {
+            keys.iter()
+                .filter_map(|key| user.sticker_replies.get_key_value(key))
+                .find(fires)
+                .map(|(k, v)| (TriggerScope::User(user_key), k, v))
+        });
+
+        user_reply
+            .or_else(|| {
+                keys.iter()
+                    .filter_map(|key| chat.sticker_replies.get_key_value(key))
+                    .find(fires)
+                    .map(|(k, v)| (TriggerScope::Common, k, v))
+            })
+            .map(|(scope, trigger, reply)| TriggerHit {
+                scope,
+                sticker: true,
+                trigger: trigger.clone(),
+                reply: reply.reply.clone(),
+            })
+    }
+
+    // Remembers that a trigger fired on a message, returns false if it already had
+    pub fn mark_fired(&mut self, chat_id: &ChatId, message_id: i32, hit: &TriggerHit) -> bool {
+        let chat = self.chat_entry(chat_id.clone());
+
+        chat.remember_fired(message_id, hit.scope.clone(), hit.trigger.clone())
+    }
+
+    // Remembers every text trigger a new message contains, ignoring probability and schedules,
+    // so editing it later only fires trigger words the edit added
+    pub fn remember_matches(&mut self, chat_id: &ChatId, user_id: u64, message_id: i32, key: &str) {
+        let Some(chat) = self.chats.get(chat_id).filter(|chat| chat.watch_edits) else {
+            return;
+        };
+
+        let lowercase_input = key.to_lowercase();
+        let contained = |replies: &HashMap<Trigger, TriggerReply>| -> Vec<Trigger> {
+            replies.keys().filter(|k| lowercase_input.contains(&k.to_lowercase())).cloned().collect()
+        };
+
+        let user_key = user_id.to_string();
+        let mut matched = Vec::new();
+        if let Some(user) = chat.users.get(&user_key) {
+            matched.extend(contained(&user.replies).into_iter().map(|t| (TriggerScope::User(user_key.clone()), t)));
+        }
+        matched.extend(contained(&chat.common_replies).into_iter().map(|t| (TriggerScope::Common, t)));
+        for name in &chat.installed_packs {
+            if let Some(pack) = self.packs.get(name) {
+                matched.extend(contained(&pack.replies).into_iter().map(|t| (TriggerScope::Pack(name.clone()), t)));
+            }
+        }
+
+        let chat = self.chats.get_mut(chat_id).unwrap();
+        for (scope, trigger) in matched {
+            chat.remember_fired(message_id, scope, trigger);
+        }
+    }
+
+    pub fn record_hit(&mut self, chat_id: &ChatId, hit: &TriggerHit, user_id: u64) {
+        let Some(chat) = self.chats.get_mut(chat_id) else {
+            return;
+        };
+
+        if let Some(reply) = chat.trigger_mut(&hit.scope, hit.sticker, &hit.trigger) {
+            reply.stats.hits += 1;
+            reply.stats.last_fired = Some(Utc::now().timestamp());
+            *reply.stats.fired_by.entry(user_id.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn get_roast_level(&self, chat_id: ChatId) -> u8 {
+        self.chats.get(&chat_id).map_or(default_roast_level(), |chat| chat.roast_level)
+    }
+
+    pub fn get_context_window(&self, chat_id: &ChatId) -> usize {
+        self.chats.get(chat_id).map_or_else(default_context_window, |chat| chat.context_window)
+    }
+}
+
+pub fn initialize_dictionary() -> Result<(), std::io::Error> {
+    let mut manager = if Path::new("dictionaries.json").exists() {
+        let data = fs::read_to_string("dictionaries.json")?;
+        println!("Read data from file: {}", data);
+
+        // Try to parse as Value first to see the raw structure
+        let json_value: serde_json::Value = serde_json::from_str(&data)?;
+        println!("Raw JSON structure: {:#?}", json_value);
+
+        // Try to parse with explicit type annotations
+        let parsed: DictionaryManager = match serde_json::from_str(&data) {
+            Ok(m) => {
+                println!("Successfully parsed dictionary");
+                m
+            }
+            Err(e) => {
+                println!("Failed to parse dictionary: {}", e);
+                DictionaryManager::default()
+            }
+        };
+        parsed
+    } else {
+        DictionaryManager::default()
+    };
+
+
+    if manager.migrate_user_keys() {
+        println!("Migrated username keyed users");
+        manager.save()?;
+    }
+
+    println!("Manager before storing: {:#?}", manager);  // Debug print
+
+    if let Ok(mut dict) = DICTIONARY.lock() {
+        *dict = Some(manager);
+        println!("Dictionary stored in static: {:#?}", dict);  // Debug print
+    }
+    Ok(())
+}
+
+
+pub fn add_user_trigger(chat_id: ChatId, target: &UserTarget, trigger: String, reply: TriggerReply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.add_user_entry(chat_id, target, trigger, reply);
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn add_common_trigger(chat_id: ChatId, trigger: String, reply: TriggerReply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.add_common_entry(chat_id, trigger, reply);
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn delete_common_trigger(chat_id: ChatId, trigger: String) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.delete_common_entry(chat_id, trigger)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn delete_user_trigger(chat_id: ChatId, target: &UserTarget, trigger: String) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.delete_user_entry(chat_id, target, trigger)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+// Adds several user or common (no target) triggers under one lock and save
+pub fn add_triggers(chat_id: ChatId, entries: Vec<(EntryTarget, Trigger, TriggerReply)>) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        for (target, trigger, reply) in entries {
+            match target {
+                EntryTarget::Common => manager.add_common_entry(chat_id.clone(), trigger, reply),
+                EntryTarget::User(target) => manager.add_user_entry(chat_id.clone(), &target, trigger, reply),
+                EntryTarget::Mention(target) => manager.add_user_mention_entry(chat_id.clone(), &target, trigger, reply),
+            }
+        }
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn copy_chat_triggers(from_chat_id: &ChatId, to_chat_id: ChatId, selected: &[Trigger], mode: ConflictMode) -> Result<CopyReport, std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    let mut report = CopyReport::default();
+    if let Some(manager) = lock.as_mut() {
+        report = manager.copy_triggers(from_chat_id, to_chat_id, selected, mode)?;
+        manager.save()?;
+    }
+    Ok(report)
+}
+
+pub fn install_chat_pack(chat_id: ChatId, name: &str) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.install_pack(chat_id, name)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn uninstall_chat_pack(chat_id: ChatId, name: &str) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.uninstall_pack(chat_id, name)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+// Adds or replaces packs in the global registry
+pub fn import_packs(packs: HashMap<PackName, Pack>) -> Result<usize, std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    let count = packs.len();
+    if let Some(manager) = lock.as_mut() {
+        manager.packs.extend(packs);
+        manager.save()?;
+    }
+    Ok(count)
+}
+
+pub fn get_packs() -> HashMap<PackName, Pack> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref().map(|manager| manager.packs.clone()).unwrap_or_default()
+    } else {
+        HashMap::new()
+    }
+}
+
+pub fn edit_trigger_reply(chat_id: ChatId, target: Option<&UserTarget>, trigger: String, reply: Reply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.edit_entry(chat_id, target, trigger, reply)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn rename_trigger_key(chat_id: ChatId, target: Option<&UserTarget>, trigger: String, new_trigger: String) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.rename_entry(chat_id, target, trigger, new_trigger)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn add_user_sticker_trigger(chat_id: ChatId, target: &UserTarget, key: StickerKey, reply: TriggerReply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.add_user_sticker_entry(chat_id, target, key, reply);
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn add_user_mention_trigger(chat_id: ChatId, target: &UserTarget, keyword: Trigger, reply: TriggerReply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.add_user_mention_entry(chat_id, target, keyword, reply);
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn delete_user_mention_trigger(chat_id: ChatId, target: &UserTarget, keyword: Trigger) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.delete_user_mention_entry(chat_id, target, keyword)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn add_common_sticker_trigger(chat_id: ChatId, key: StickerKey, reply: TriggerReply) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.add_common_sticker_entry(chat_id, key, reply);
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn delete_user_sticker_trigger(chat_id: ChatId, target: &UserTarget, key: StickerKey) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.delete_user_sticker_entry(chat_id, target, key)?;
+        manager.save()?;
+    }
+    Ok(())
+}
+
+pub fn delete_common_sticker_trigger(chat_id: ChatId, key: StickerKey) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        user.replies.remove(&trigger);
+    if let Some(manager) = lock.as_mut() {
+        manager.delete_common_sticker_entry(chat_id, key)?;
+        manager.save()?;
     }
+    Ok(())
+}
 
-    pub fn add_common_entry(&mut self, chat_id: ChatId, trigger: String, reply: String) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
+pub fn update_reply_frequency(chat_id: ChatId, reply_frq: u32) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        if chat.reply_frequency == 0 {
-            chat.reply_frequency = default_reply_frequency()
-        }
-        
-        chat.common_replies.insert(trigger, reply);
+    if let Some(manager) = lock.as_mut() {
+        manager.update_reply_freq(chat_id, reply_frq);
+        manager.save()?;
     }
+    Ok(())
+}
 
-    pub fn delete_common_entry(&mut self, chat_id: ChatId, trigger: String) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
+pub fn update_counter_mode(chat_id: ChatId, counter_mode: CounterMode) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        chat.common_replies.remove(&trigger);
+    if let Some(manager) = lock.as_mut() {
+        manager.update_counter_mode(chat_id, counter_mode);
+        manager.save()?;
     }
+    Ok(())
+}
 
-    pub fn update_reply_freq(&mut self, chat_id: ChatId, reply_frq: u32) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
+// Counters are saved with the next trigger hit rather than on every message
+pub fn count_chat_message(chat_id: &ChatId, user_id: u64) {
+    if let Ok(mut lock) = DICTIONARY.lock() {
+        if let Some(manager) = lock.as_mut() {
+            manager.count_message(chat_id, user_id);
+        }
+    }
+}
 
-        chat.reply_frequency = reply_frq;
+pub fn update_reply_policy(chat_id: ChatId, reply_policy: ReplyPolicy) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.update_reply_policy(chat_id, reply_policy);
+        manager.save()?;
     }
+    Ok(())
+}
 
-    pub fn update_roast_level(&mut self, chat_id: ChatId, roast_level: u8) {
-        let chat = self.chats.entry(chat_id).or_insert_with(|| Chat {
-            message_counter: 0,
-            reply_frequency: default_reply_frequency(),
-            roast_level: default_roast_level(),
-            name: "New Chat".to_string(),
-            users: HashMap::new(),
-            common_replies: HashMap::new(),
-        });
+pub fn update_user_reply_frequency(chat_id: ChatId, target: &UserTarget, reply_frq: Option<u32>) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        chat.roast_level = roast_level;
+    if let Some(manager) = lock.as_mut() {
+        manager.update_user_reply_freq(chat_id, target, reply_frq)?;
+        manager.save()?;
     }
+    Ok(())
+}
 
-    pub fn get_response(&self, chat_id: ChatId, username: Username, key: String) -> Option<&String> {
-        let chat = self.chats.get(&chat_id)?;
-        let user = chat.users.get(&username)?;
+pub fn add_user_profile_note(chat_id: ChatId, target: &UserTarget, note: String) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        let lowercase_input = key.to_lowercase();
+    if let Some(manager) = lock.as_mut() {
+        manager.add_profile_note(chat_id, target, note)?;
+        manager.save()?;
+    }
+    Ok(())
+}
 
-        let user_reply = user.replies
-            .iter()
-            .find(|(k, _)| lowercase_input.contains(&k.to_lowercase()))
-            .map(|(_, v)| v);
+pub fn clear_user_profile(chat_id: &ChatId, target: &UserTarget) -> Result<bool, std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        match user_reply {
-            Some(reply) => Some(reply),
-            None => chat.common_replies
-                .iter()
-                .find(|(k, _)| lowercase_input.contains(&k.to_lowercase()))
-                .map(|(_, v)| v)
+    if let Some(manager) = lock.as_mut() {
+        if manager.clear_profile(chat_id, target) {
+            manager.save()?;
+            return Ok(true);
         }
     }
+    Ok(false)
+}
 
-    pub fn get_roast_level(&self, chat_id: ChatId) -> u8 {
-        let chat = self.chats.get(&chat_id);
-        
-        chat.unwrap().roast_level
+pub fn get_user_profile(chat_id: &ChatId, target: &UserTarget) -> Vec<String> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref().map(|manager| manager.get_profile(chat_id, target)).unwrap_or_default()
+    } else {
+        Vec::new()
     }
 }
 
-pub fn initialize_dictionary() -> Result<(), std::io::Error> {
-    let manager = if Path::new("dictionaries.json").exists() {
-        let data = fs::read_to_string("dictionaries.json")?;
-        println!("Read data from file: {}", data);
+pub fn update_roast_level(chat_id: ChatId, roast_level: u8) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-        // Try to parse as Value first to see the raw structure
-        let json_value: serde_json::Value = serde_json::from_str(&data)?;
-        println!("Raw JSON structure: {:#?}", json_value);
+    if let Some(manager) = lock.as_mut() {
+        manager.update_roast_level(chat_id, roast_level);
+        manager.save()?;
+    }
+    Ok(())
+}
 
-        // Try to parse with explicit type annotations
-        let parsed: DictionaryManager = match serde_json::from_str(&data) {
-            Ok(m) => {
-                println!("Successfully parsed dictionary");
-                m
-            }
-            Err(e) => {
-                println!("Failed to parse dictionary: {}", e);
-                DictionaryManager::default()
-            }
-        };
-        parsed
-    } else {
-        DictionaryManager::default()
-    };
+pub fn update_context_window(chat_id: ChatId, context_window: usize) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
+    if let Some(manager) = lock.as_mut() {
+        manager.update_context_window(chat_id, context_window);
+        manager.save()?;
+    }
+    Ok(())
+}
 
-    println!("Manager before storing: {:#?}", manager);  // Debug print
+pub fn update_utc_offset(chat_id: ChatId, utc_offset: i32) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
 
-    if let Ok(mut dict) = DICTIONARY.lock() {
-        *dict = Some(manager);
-        println!("Dictionary stored in static: {:#?}", dict);  // Debug print
+    if let Some(manager) = lock.as_mut() {
+        manager.update_utc_offset(chat_id, utc_offset);
+        manager.save()?;
     }
     Ok(())
 }
 
-
-pub fn add_user_trigger(chat_id: ChatId, username: Username, trigger: String, reply: String) -> Result<(), std::io::Error> {
+pub fn update_quiet_hours(chat_id: ChatId, quiet_hours: Option<ActiveWindow>) -> Result<(), std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
     if let Some(manager) = lock.as_mut() {
-        manager.add_user_entry(chat_id, username, trigger, reply);
+        manager.update_quiet_hours(chat_id, quiet_hours);
         manager.save()?;
     }
     Ok(())
 }
 
-pub fn add_common_trigger(chat_id: ChatId, trigger: String, reply: String) -> Result<(), std::io::Error> {
+pub fn update_silenced_until(chat_id: ChatId, silenced_until: Option<i64>) -> Result<(), std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
     if let Some(manager) = lock.as_mut() {
-        manager.add_common_entry(chat_id, trigger, reply);
+        manager.update_silenced_until(chat_id, silenced_until);
         manager.save()?;
     }
     Ok(())
 }
 
-pub fn delete_common_trigger(chat_id: ChatId, trigger: String) -> Result<(), std::io::Error> {
+pub fn update_watch_edits(chat_id: ChatId, watch_edits: bool, edit_reply: Option<Reply>) -> Result<(), std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
     if let Some(manager) = lock.as_mut() {
-        manager.delete_common_entry(chat_id, trigger);
+        manager.update_watch_edits(chat_id, watch_edits, edit_reply);
         manager.save()?;
     }
     Ok(())
 }
 
-pub fn delete_user_trigger(chat_id: ChatId, username: Username, trigger: String) -> Result<(), std::io::Error> {
+pub fn prune_expired_triggers() -> Result<Vec<(ChatId, Vec<Trigger>)>, std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
+    let mut pruned = Vec::new();
     if let Some(manager) = lock.as_mut() {
-        manager.delete_user_entry(chat_id, username, trigger);
-        manager.save()?;
+        pruned = manager.prune_expired(Utc::now().timestamp());
+        if !pruned.is_empty() {
+            manager.save()?;
+        }
+    }
+    Ok(pruned)
+}
+
+pub fn resolve_chat_user(chat_id: &ChatId, target: &UserTarget) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        if manager.resolve_user(chat_id, target) {
+            manager.save()?;
+        }
     }
     Ok(())
 }
 
-pub fn update_reply_frequency(chat_id: ChatId, reply_frq: u32) -> Result<(), std::io::Error> {
+pub fn match_chat_dialog(chat_id: &ChatId, user_id: u64, replied_message_id: Option<i32>, text: &str) -> Result<Option<DialogMatch>, std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
+    let mut dialog_match = None;
     if let Some(manager) = lock.as_mut() {
-        manager.update_reply_freq(chat_id, reply_frq);
+        let had_state = manager.chats.get(chat_id).is_some_and(|chat| chat.dialog_states.contains_key(&user_id.to_string()));
+        dialog_match = manager.match_dialog(chat_id, user_id, replied_message_id, text);
+        if had_state {
+            manager.save()?;
+        }
+    }
+    Ok(dialog_match)
+}
+
+pub fn continue_chat_dialog(chat_id: &ChatId, user_id: u64, dialog: DialogName, step: usize, bot_message_id: i32) -> Result<(), std::io::Error> {
+    let mut lock = DICTIONARY.lock().map_err(|e| {
+        std::io::Error::other(e.to_string())
+    })?;
+
+    if let Some(manager) = lock.as_mut() {
+        manager.continue_dialog(chat_id, user_id, dialog, step, bot_message_id);
         manager.save()?;
     }
     Ok(())
 }
 
-pub fn update_roast_level(chat_id: ChatId, roast_level: u8) -> Result<(), std::io::Error> {
+pub fn import_chat_dialogs(chat_id: ChatId, dialogs: HashMap<DialogName, Dialog>) -> Result<usize, std::io::Error> {
     let mut lock = DICTIONARY.lock().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })?;
 
+    let mut count = 0;
     if let Some(manager) = lock.as_mut() {
-        manager.update_roast_level(chat_id, roast_level);
+        count = manager.import_dialogs(chat_id, dialogs);
         manager.save()?;
     }
-    Ok(())
+    Ok(count)
+}
+
+pub fn get_chat(chat_id: &ChatId) -> Option<Chat> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref()?.chats.get(chat_id).cloned()
+    } else {
+        None
+    }
+}
+
+pub fn is_chat_quiet(chat_id: &ChatId) -> bool {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref()
+            .and_then(|manager| manager.chats.get(chat_id))
+            .is_some_and(|chat| chat.is_quiet())
+    } else {
+        false
+    }
+}
+
+pub fn get_dictionary_response(chat_id: ChatId, user_id: u64, key: String, kind: ContentKind) -> Option<TriggerHit> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref()?.get_response(chat_id, user_id, key, kind)
+    } else {
+        None
+    }
+}
+
+pub fn remember_message_triggers(chat_id: &ChatId, user_id: u64, message_id: i32, key: &str) {
+    if let Ok(mut lock) = DICTIONARY.lock() {
+        if let Some(manager) = lock.as_mut() {
+            manager.remember_matches(chat_id, user_id, message_id, key);
+        }
+    }
+}
+
+// Triggers that haven't fired on this message yet, so an edit can only add new ones
+pub fn get_unfired_dictionary_response(chat_id: ChatId, user_id: u64, message_id: i32, key: String, kind: ContentKind) -> Option<TriggerHit> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        let manager = lock.as_ref()?;
+        let chat = manager.chats.get(&chat_id)?;
+        manager.get_response_excluding(chat_id.clone(), user_id, key, kind, |scope, trigger| chat.has_fired(message_id, scope, trigger))
+    } else {
+        None
+    }
+}
+
+pub fn get_mention_dictionary_response(chat_id: ChatId, user_id: u64, targets: &[UserTarget], key: String, kind: ContentKind) -> Option<TriggerHit> {
+    if let Ok(lock) = DICTIONARY.lock() {
+        lock.as_ref()?.get_mention_response(chat_id, user_id, targets, key, kind)
+    } else {
+        None
+    }
 }
 
-pub fn get_dictionary_response(chat_id: ChatId, username: Username, key: String) -> Option<String> {
+pub fn get_sticker_dictionary_response(chat_id: ChatId, user_id: u64, keys: &[StickerKey]) -> Option<TriggerHit> {
     if let Ok(lock) = DICTIONARY.lock() {
-        lock.as_ref()?.get_response(chat_id, username, key).cloned()
+        lock.as_ref()?.get_sticker_response(chat_id, user_id, keys)
     } else {
         None
     }
@@ -321,9 +2011,9 @@ pub fn print_dictionary() {
             for (chat_id, chat) in chats {
                 println!("Chat ID {}: Chat name {:#?}", chat_id, chat.name);
                 for (user_id, user) in &chat.users {
-                    println!("User ID {}: User full name {:#?}", user_id, user.fullname);
+                    println!("User ID {}: User full name {:#?} (@{})", user_id, user.fullname, user.username.as_deref().unwrap_or("-"));
                     for (trigger, reply) in &user.replies {
-                        println!("Trigger {}: Reply {:#?}", trigger, reply);
+                        println!("Trigger {}: Reply {:#?} ({}%)", trigger, reply.reply, reply.probability);
                     }
                 }
             }
@@ -334,3 +2024,238 @@ pub fn print_dictionary() {
         println!("Failed to lock dictionary");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::time::{parse_duration, parse_hours, parse_utc_offset, parse_weekdays};
+    use chrono::TimeZone;
+
+    const LEGACY: &str = r#"{
+        "chats": {
+            "-100": {
+                "message_counter": 0,
+                "reply_frequency": 3,
+                "name": "Test chat",
+                "users": {
+                    "bob": {
+                        "fullname": "Bob",
+                        "replies": { "кек": "бек" }
+                    }
+                },
+                "common_replies": { "поху": "олай айтпа" }
+            }
+        }
+    }"#;
+
+    fn member(id: u64, username: &str) -> UserTarget {
+        UserTarget::Member {
+            id,
+            username: Some(username.to_string()),
+            fullname: username.to_string(),
+        }
+    }
+
+    #[test]
+    fn committed_dictionary_migrates_to_username_keys() {
+        let mut manager: DictionaryManager = serde_json::from_str(include_str!("../../dictionaries.json")).unwrap();
+        manager.migrate_user_keys();
+
+        for chat in manager.chats.values() {
+            for (key, user) in &chat.users {
+                assert!(key.parse::<u64>().is_ok() || key.starts_with('@'), "legacy key {} left", key);
+                if let Some(username) = key.strip_prefix('@') {
+                    assert_eq!(user.username.as_deref(), Some(username));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_user_is_claimed_by_id_on_first_message() {
+        let mut manager: DictionaryManager = serde_json::from_str(LEGACY).unwrap();
+        assert!(manager.migrate_user_keys());
+        assert!(!manager.migrate_user_keys());
+
+        let chat_id = "-100".to_string();
+        let chat = &manager.chats[&chat_id];
+        assert_eq!(chat.users["@bob"].username.as_deref(), Some("bob"));
+        assert_eq!(chat.users["@bob"].replies["кек"].reply, "бек");
+
+        assert!(manager.resolve_user(&chat_id, &member(42, "Bob")));
+        assert!(!manager.resolve_user(&chat_id, &member(42, "Bob")));
+
+        let chat = &manager.chats[&chat_id];
+        assert!(!chat.users.contains_key("@bob"));
+        assert_eq!(chat.users["42"].replies["кек"].reply, "бек");
+        assert_eq!(chat.users["42"].fullname, "Bob");
+        assert_eq!(chat.find_user_key(&UserTarget::Username("bob".to_string())).as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn resolve_user_ignores_unknown_members() {
+        let mut manager: DictionaryManager = serde_json::from_str(LEGACY).unwrap();
+        manager.migrate_user_keys();
+        let chat_id = "-100".to_string();
+
+        assert!(!manager.resolve_user(&chat_id, &member(7, "alice")));
+        assert!(!manager.chats[&chat_id].users.contains_key("7"));
+    }
+
+    #[test]
+    fn username_moving_to_another_member_stays_with_the_first_members_triggers() {
+        let mut chat = Chat::default();
+        chat.user_entry(&member(1, "bob")).replies.insert("кек".to_string(), TriggerReply::new("бек".to_string()));
+
+        // Member 1 renamed and member 2 took "bob"
+        let user = chat.user_entry(&member(2, "bob"));
+        assert!(user.replies.is_empty());
+
+        assert!(chat.users["1"].username.is_none());
+        assert_eq!(chat.users["1"].replies["кек"].reply, "бек");
+        assert_eq!(chat.find_user_key(&UserTarget::Username("bob".to_string())).as_deref(), Some("2"));
+
+        chat.user_entry(&member(1, "robert"));
+        assert_eq!(chat.find_user_key(&UserTarget::Username("robert".to_string())).as_deref(), Some("1"));
+        assert_eq!(chat.users.len(), 2);
+    }
+
+    // 2026-10-16 is a Friday
+    fn local(day: u32, hour: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn window_past_midnight_belongs_to_the_day_it_started() {
+        let friday_night = ActiveWindow { days: parse_weekdays("fri").unwrap(), start_hour: 22, end_hour: 2 };
+
+        assert!(friday_night.contains(&local(16, 23)));
+        assert!(friday_night.contains(&local(17, 1)));
+        assert!(!friday_night.contains(&local(16, 1)));
+        assert!(!friday_night.contains(&local(17, 23)));
+        assert!(!friday_night.contains(&local(17, 2)));
+    }
+
+    #[test]
+    fn window_within_a_day() {
+        let evenings = ActiveWindow { days: Vec::new(), start_hour: 18, end_hour: 23 };
+
+        assert!(evenings.contains(&local(14, 18)));
+        assert!(!evenings.contains(&local(14, 23)));
+        assert!(!evenings.contains(&local(14, 1)));
+    }
+
+    #[test]
+    fn parses_hour_ranges() {
+        assert_eq!(parse_hours("18-23"), Some((18, 23)));
+        assert_eq!(parse_hours("22-2"), Some((22, 2)));
+        assert_eq!(parse_hours("0-24"), Some((0, 24)));
+        assert_eq!(parse_hours("24-8"), None);
+        assert_eq!(parse_hours("8-8"), None);
+        assert_eq!(parse_hours("8-25"), None);
+        assert_eq!(parse_hours("evening"), None);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30m"), Some(30 * 60));
+        assert_eq!(parse_duration("2w"), Some(2 * 7 * 24 * 60 * 60));
+        assert_eq!(parse_duration("366d"), Some(366 * 24 * 60 * 60));
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("367d"), None);
+        assert_eq!(parse_duration("9223372036000000000s"), None);
+        assert_eq!(parse_duration("99999999999999999w"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn parses_weekdays() {
+        assert_eq!(parse_weekdays("mon-fri"), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(parse_weekdays("fri-mon"), Some(vec![0, 4, 5, 6]));
+        assert_eq!(parse_weekdays("sat,SUN"), Some(vec![5, 6]));
+        assert_eq!(parse_weekdays("funday"), None);
+    }
+
+    #[test]
+    fn parses_utc_offsets() {
+        assert_eq!(parse_utc_offset("+5"), Some(5 * 60));
+        assert_eq!(parse_utc_offset("-3:30"), Some(-(3 * 60 + 30)));
+        assert_eq!(parse_utc_offset("UTC+14"), Some(14 * 60));
+        assert_eq!(parse_utc_offset("+15"), None);
+        assert_eq!(parse_utc_offset("+5:60"), None);
+        assert_eq!(parse_utc_offset("+99999999"), None);
+        assert_eq!(parse_utc_offset("-2147483648"), None);
+    }
+
+    #[test]
+    fn token_bucket_fills_on_first_use() {
+        let mut budget = ReplyBudget::default();
+        let now = 1_800_000_000;
+
+        assert!(budget.take_token(3, 600, now));
+        assert_eq!(budget.tokens, 2);
+        assert!(budget.take_token(3, 600, now));
+        assert!(budget.take_token(3, 600, now));
+        assert!(!budget.take_token(3, 600, now));
+    }
+
+    #[test]
+    fn token_bucket_refills_after_refill_seconds() {
+        let mut budget = ReplyBudget::default();
+        let now = 1_800_000_000;
+        while budget.take_token(2, 600, now) {}
+
+        assert!(!budget.take_token(2, 600, now + 599));
+        assert!(budget.take_token(2, 600, now + 600));
+        assert!(!budget.take_token(2, 600, now + 600));
+
+        // A long pause refills up to capacity, not beyond
+        assert!(budget.take_token(2, 600, now + 100 * 600));
+        assert!(budget.take_token(2, 600, now + 100 * 600));
+        assert!(!budget.take_token(2, 600, now + 100 * 600));
+    }
+
+    #[test]
+    fn matched_counter_fires_every_nth_match() {
+        let mut counter = 0;
+        let fired: Vec<bool> = (0..6).map(|_| CounterMode::Matched.fires(&mut counter, 3)).collect();
+
+        assert_eq!(fired, vec![false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn all_counter_resets_after_firing() {
+        let mut counter = 2;
+        assert!(!CounterMode::All.fires(&mut counter, 3));
+        assert_eq!(counter, 2);
+
+        counter = 3;
+        assert!(CounterMode::All.fires(&mut counter, 3));
+        assert_eq!(counter, 0);
+        assert!(!CounterMode::All.fires(&mut counter, 3));
+    }
+
+    #[test]
+    fn edits_only_fire_triggers_they_added() {
+        let chat_id = "-100".to_string();
+        let mut chat = Chat { watch_edits: true, ..Chat::new("Test chat".to_string()) };
+        for trigger in ["кек", "лол", "хаха"] {
+            chat.common_replies.insert(trigger.to_string(), TriggerReply::new(format!("{} reply", trigger)));
+        }
+        let mut manager = DictionaryManager::default();
+        manager.chats.insert(chat_id.clone(), chat);
+
+        manager.remember_matches(&chat_id, 1, 10, "кек и лол");
+
+        let unfired = |manager: &DictionaryManager, text: &str| {
+            let chat = &manager.chats[&chat_id];
+            manager.get_response_excluding(chat_id.clone(), 1, text.to_string(), ContentKind::Text, |scope, trigger| {
+                chat.has_fired(10, scope, trigger)
+            })
+        };
+        assert!(unfired(&manager, "кек и лолл").is_none());
+        assert_eq!(unfired(&manager, "кек и лол хаха").map(|hit| hit.trigger).as_deref(), Some("хаха"));
+    }
+}
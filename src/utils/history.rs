@@ -0,0 +1,59 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+// Most messages kept per chat, whatever the chat's window setting
+pub const MAX_HISTORY: usize = 50;
+// Rough prompt budget for the recent messages, at about 4 characters per token
+const TOKEN_BUDGET: usize = 400;
+const CHARS_PER_TOKEN: usize = 4;
+pub const MAX_LINE_CHARS: usize = 300;
+
+#[derive(Debug, Clone)]
+pub struct HistoryLine {
+    pub message_id: i32,
+    pub author: String,
+    pub text: String,
+}
+
+// Recent messages per chat, kept in memory only
+static HISTORY: Lazy<Mutex<HashMap<i64, VecDeque<HistoryLine>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn record_message(chat_id: i64, message_id: i32, author: &str, text: &str) {
+    let mut history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let lines = history.entry(chat_id).or_default();
+
+    if lines.len() >= MAX_HISTORY {
+        lines.pop_front();
+    }
+    lines.push_back(HistoryLine {
+        message_id,
+        author: author.to_string(),
+        text: text.chars().take(MAX_LINE_CHARS).collect(),
+    });
+}
+
+// Up to `window` messages before `before_message_id`, oldest first, trimmed to the token budget
+pub fn recent_messages(chat_id: i64, before_message_id: i32, window: usize) -> Vec<HistoryLine> {
+    let history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(lines) = history.get(&chat_id) else {
+        return Vec::new();
+    };
+
+    let mut budget = TOKEN_BUDGET * CHARS_PER_TOKEN;
+    let mut recent: Vec<HistoryLine> = lines
+        .iter()
+        .rev()
+        .filter(|line| line.message_id != before_message_id)
+        .take(window)
+        .take_while(|line| {
+            let cost = line.author.chars().count() + line.text.chars().count();
+            budget = budget.saturating_sub(cost);
+            budget > 0
+        })
+        .cloned()
+        .collect();
+
+    recent.reverse();
+    recent
+}
@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub type LlmError = Box<dyn std::error::Error + Send + Sync>;
+
+const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
+const DEEPSEEK_MODEL: &str = "deepseek-chat";
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 15;
+
+// Sampling settings shared by every provider
+#[derive(Debug, Clone)]
+pub struct CompletionOptions {
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub top_p: f32,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    // Shown in logs, e.g. "deepseek/deepseek-chat"
+    fn name(&self) -> String;
+
+    async fn complete(&self, system_prompt: &str, prompt: &str, options: &CompletionOptions) -> Result<String, LlmError>;
+}
+
+// Any endpoint speaking the OpenAI chat completions API, DeepSeek included
+pub struct OpenAiCompatibleProvider {
+    client: Client,
+    label: String,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(label: &str, base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            label: label.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> String {
+        format!("{}/{}", self.label, self.model)
+    }
+
+    async fn complete(&self, system_prompt: &str, prompt: &str, options: &CompletionOptions) -> Result<String, LlmError> {
+        let payload = json!({
+            "model": self.model,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": system_prompt
+                },
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ],
+            "max_tokens": options.max_tokens,
+            "temperature": options.temperature,
+            "top_p": options.top_p
+        });
+
+        let mut request = self.client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Content-Type", "application/json")
+            .json(&payload);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(format!("{} API error: {}", self.label, response.status()).into());
+        }
+
+        let json: Value = response.json().await?;
+        json["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No content in API response".into())
+    }
+}
+
+// Local Ollama style server using its native /api/chat endpoint
+pub struct OllamaProvider {
+    client: Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: &str, model: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> String {
+        format!("ollama/{}", self.model)
+    }
+
+    async fn complete(&self, system_prompt: &str, prompt: &str, options: &CompletionOptions) -> Result<String, LlmError> {
+        let payload = json!({
+            "model": self.model,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": system_prompt
+                },
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ],
+            "stream": false,
+            "options": {
+                "num_predict": options.max_tokens,
+                "temperature": options.temperature,
+                "top_p": options.top_p
+            }
+        });
+
+        let response = self.client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&payload)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Ollama API error: {}", response.status()).into());
+        }
+
+        let json: Value = response.json().await?;
+        json["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No content in API response".into())
+    }
+}
+
+// Tries providers in order until one answers within its timeout
+pub struct LlmChain {
+    entries: Vec<ChainEntry>,
+}
+
+struct ChainEntry {
+    provider: Arc<dyn LlmProvider>,
+    timeout: Duration,
+}
+
+// Outcome counters per provider since startup
+#[derive(Debug, Clone, Default)]
+pub struct ProviderMetrics {
+    pub successes: u32,
+    pub failures: u32,
+    pub timeouts: u32,
+    pub total_latency_ms: u64,
+}
+
+static METRICS: Lazy<Mutex<HashMap<String, ProviderMetrics>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn record_metric(provider: &str, update: impl FnOnce(&mut ProviderMetrics)) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    update(metrics.entry(provider.to_string()).or_default());
+}
+
+pub fn provider_metrics() -> Vec<(String, ProviderMetrics)> {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut metrics: Vec<_> = metrics.iter().map(|(name, m)| (name.clone(), m.clone())).collect();
+    metrics.sort_by(|a, b| a.0.cmp(&b.0));
+    metrics
+}
+
+#[async_trait]
+impl LlmProvider for LlmChain {
+    fn name(&self) -> String {
+        self.entries.iter().map(|entry| entry.provider.name()).collect::<Vec<_>>().join(" -> ")
+    }
+
+    async fn complete(&self, system_prompt: &str, prompt: &str, options: &CompletionOptions) -> Result<String, LlmError> {
+        let mut last_error: LlmError = "No LLM providers configured".into();
+
+        for entry in &self.entries {
+            let name = entry.provider.name();
+            let started = Instant::now();
+
+            match tokio::time::timeout(entry.timeout, entry.provider.complete(system_prompt, prompt, options)).await {
+                Ok(Ok(content)) => {
+                    let latency = started.elapsed().as_millis() as u64;
+                    log::info!("LLM completion by {} in {}ms", name, latency);
+                    record_metric(&name, |m| {
+                        m.successes += 1;
+                        m.total_latency_ms += latency;
+                    });
+                    return Ok(content);
+                }
+                Ok(Err(e)) => {
+                    log::warn!("LLM provider {} failed: {}", name, e);
+                    record_metric(&name, |m| m.failures += 1);
+                    last_error = e;
+                }
+                Err(_) => {
+                    log::warn!("LLM provider {} timed out after {}s", name, entry.timeout.as_secs());
+                    record_metric(&name, |m| m.timeouts += 1);
+                    last_error = format!("{} timed out", name).into();
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+// LLM_{KIND}_{SETTING} first, then the shared LLM_{SETTING}; empty values count as unset
+fn setting(kind: &str, name: &str) -> Option<String> {
+    [format!("LLM_{}_{}", kind.to_uppercase(), name), format!("LLM_{}", name)]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|value| !value.trim().is_empty()))
+}
+
+// One "kind" or "kind:model" entry of LLM_PROVIDER
+fn provider_from_entry(entry: &str) -> Result<ChainEntry, String> {
+    let (kind, model) = match entry.split_once(':') {
+        Some((kind, model)) => (kind.trim().to_lowercase(), Some(model.trim().to_string())),
+        None => (entry.trim().to_lowercase(), None),
+    };
+    let model = model.or_else(|| setting(&kind, "MODEL"));
+    let base_url = setting(&kind, "BASE_URL");
+    let api_key = setting(&kind, "API_KEY");
+
+    let timeout = setting(&kind, "TIMEOUT")
+        .map(|value| value.parse::<u64>().map_err(|_| format!("Invalid timeout '{}' for {}", value, kind)))
+        .transpose()?
+        .unwrap_or(DEFAULT_TIMEOUT_SECONDS);
+
+    let provider: Arc<dyn LlmProvider> = match kind.as_str() {
+        "deepseek" => {
+            let api_key = api_key
+                .or_else(|| env::var("DEEPSEEK_API_KEY").ok())
+                .ok_or("DEEPSEEK_API_KEY environment variable not set")?;
+            Arc::new(OpenAiCompatibleProvider::new(
+                "deepseek",
+                base_url.as_deref().unwrap_or(DEEPSEEK_BASE_URL),
+                model.as_deref().unwrap_or(DEEPSEEK_MODEL),
+                Some(api_key),
+            ))
+        }
+        "openai" => {
+            let base_url = base_url.ok_or("LLM_OPENAI_BASE_URL environment variable not set")?;
+            let model = model.ok_or("LLM_OPENAI_MODEL environment variable not set")?;
+            Arc::new(OpenAiCompatibleProvider::new("openai", &base_url, &model, api_key))
+        }
+        "ollama" => {
+            let model = model.ok_or("LLM_OLLAMA_MODEL environment variable not set")?;
+            Arc::new(OllamaProvider::new(base_url.as_deref().unwrap_or(OLLAMA_BASE_URL), &model))
+        }
+        other => return Err(format!("Unknown LLM provider '{}', expected deepseek, openai or ollama", other)),
+    };
+
+    Ok(ChainEntry { provider, timeout: Duration::from_secs(timeout) })
+}
+
+// Builds the chain from LLM_PROVIDER, a comma separated list like "deepseek, ollama:llama3".
+// Providers that fail to configure are skipped so the rest of the chain still works.
+pub fn provider_from_env() -> Result<Arc<dyn LlmProvider>, String> {
+    let providers = env::var("LLM_PROVIDER")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "deepseek".to_string());
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for entry in providers.split(',').filter(|entry| !entry.trim().is_empty()) {
+        match provider_from_entry(entry) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(errors.join("; "));
+    }
+    for e in errors {
+        log::warn!("Skipping LLM provider: {}", e);
+    }
+    Ok(Arc::new(LlmChain { entries }))
+}
@@ -1,2 +1,9 @@
 pub mod dictionary;
-pub mod deepseek;
\ No newline at end of file
+pub mod roast;
+pub mod llm;
+pub mod templates;
+pub mod history;
+pub mod time;
+pub mod scheduler;
+pub mod sticker;
+pub mod rate_limit;
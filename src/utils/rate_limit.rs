@@ -0,0 +1,112 @@
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use teloxide::payloads::{SendDocument, SendMessage};
+use teloxide::requests::{Output, Request};
+use teloxide::types::Recipient;
+use teloxide::prelude::ResponseResult;
+use teloxide::RequestError;
+
+const WINDOW: Duration = Duration::from_secs(60);
+// Telegram allows about 20 messages per minute in a group
+const DEFAULT_MESSAGES_PER_MINUTE: usize = 20;
+const MAX_RETRIES: usize = 3;
+
+// When each chat was last sent to within the current window
+static SENT: Lazy<Mutex<HashMap<Recipient, VecDeque<Instant>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn messages_per_minute() -> usize {
+    env::var("OUTGOING_MESSAGES_PER_MINUTE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_MESSAGES_PER_MINUTE)
+}
+
+// Messages over the budget are dropped unless OUTGOING_OVERFLOW=queue.
+// Queueing sleeps inside the update handler, and teloxide handles a chat's updates
+// one at a time, so a flood also delays that chat's commands (/shutup included).
+fn drops_overflow() -> bool {
+    env::var("OUTGOING_OVERFLOW").map_or(true, |value| value != "queue")
+}
+
+// Takes a slot in the chat's budget, waiting for one to free up unless overflow is dropped
+async fn acquire(chat: &Recipient) -> bool {
+    loop {
+        let wait = {
+            let mut sent = SENT.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+
+            // Forget chats with nothing sent in the current window
+            sent.retain(|_, window| window.back().is_some_and(|sent_at| now.duration_since(*sent_at) < WINDOW));
+            let window = sent.entry(chat.clone()).or_default();
+
+            while window.front().is_some_and(|sent_at| now.duration_since(*sent_at) >= WINDOW) {
+                window.pop_front();
+            }
+            if window.len() < messages_per_minute() {
+                window.push_back(now);
+                return true;
+            }
+            if drops_overflow() {
+                return false;
+            }
+            window.front().map(|sent_at| WINDOW.saturating_sub(now.duration_since(*sent_at))).unwrap_or_default()
+        };
+
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// Outgoing requests that count against a chat's budget
+pub trait Outgoing {
+    fn recipient(&self) -> &Recipient;
+}
+
+impl Outgoing for SendMessage {
+    fn recipient(&self) -> &Recipient {
+        &self.chat_id
+    }
+}
+
+impl Outgoing for SendDocument {
+    fn recipient(&self) -> &Recipient {
+        &self.chat_id
+    }
+}
+
+pub trait RateLimitExt: Request {
+    // Sends within the chat's budget, retrying after Telegram's RetryAfter.
+    // Resolves to None when the message was dropped for going over the budget.
+    fn limited(self) -> impl Future<Output = ResponseResult<Option<Output<Self>>>> + Send;
+}
+
+impl<R> RateLimitExt for R
+where
+    R: Request<Err = RequestError> + Send + Sync,
+    R::Payload: Outgoing,
+    Output<R>: Send,
+{
+    async fn limited(self) -> ResponseResult<Option<Output<Self>>> {
+        let chat = self.payload_ref().recipient().clone();
+        if !acquire(&chat).await {
+            log::warn!("Outgoing budget exceeded for chat {}, message dropped", chat);
+            return Ok(None);
+        }
+
+        let mut retries = 0;
+        loop {
+            match self.send_ref().await {
+                Err(RequestError::RetryAfter(seconds)) if retries < MAX_RETRIES => {
+                    log::warn!("Rate limited in chat {}, retrying after {}s", chat, seconds.seconds());
+                    tokio::time::sleep(seconds.duration()).await;
+                    retries += 1;
+                }
+                result => return result.map(Some),
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use crate::utils::history::HistoryLine;
+use crate::utils::templates::{context_by_level, MessageFeatures};
+use crate::utils::llm::{provider_from_env, CompletionOptions, LlmError, LlmProvider};
+
+// Writes roasts through the configured chain of LLM providers
+#[derive(Clone)]
+pub struct Roaster {
+    provider: Arc<dyn LlmProvider>,
+}
+
+impl Roaster {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            provider: provider_from_env()?,
+        })
+    }
+
+    pub fn provider_name(&self) -> String {
+        self.provider.name()
+    }
+
+    pub async fn generate_roast(&self, message: &str, username: &str, level: u8, recent: &[HistoryLine], replied: Option<&HistoryLine>, profile: &[String]) -> Result<String, LlmError> {
+        let prompt = self.create_roast_prompt(message, username, level, recent, replied);
+        let system_prompt = self.create_system_prompt(level, profile);
+
+        let options = CompletionOptions {
+            max_tokens: 90,
+            temperature: 0.9,
+            top_p: 0.9,
+        };
+
+        let content = self.provider.complete(&system_prompt, &prompt, &options).await?;
+        let cleaned_content = content.trim()
+            .trim_matches('"')
+            .trim_matches('\'')
+            .trim();
+        Ok(cleaned_content.to_string())
+    }
+
+    fn create_system_prompt(&self, level: u8, profile: &[String]) -> String {
+        let level_clamped = level.clamp(1, 5);
+
+        let prompt = match level_clamped {
+            1 => {
+                "You are a gentle, playful roast bot for a Telegram chat where people speak Kazakh, Russian, and English. Generate kind but teasing roasts that are lighthearted and friendly. Always respond in the SAME LANGUAGE as the user's message. Be sweet and playful, like friendly banter between friends. Keep responses under 120 characters. DO NOT use any emojis or profanity. NEVER use quotes around your response - respond directly without any formatting."
+            },
+            2 => {
+                "You are a mildly sarcastic roast bot for a Telegram chat where people speak Kazakh, Russian, and English. Generate gentle roasts with light sarcasm that are still friendly. Always respond in the SAME LANGUAGE as the user's message. Be witty but keep it light and harmless. Keep responses under 120 characters. DO NOT use any emojis or strong profanity. NEVER use quotes around your response - respond directly without any formatting."
+            },
+            3 => {
+                "You are a moderately sarcastic roast bot for a Telegram chat where people speak Kazakh, Russian, and English. Generate witty, satirical roasts that are moderately sharp and clever. Always respond in the SAME LANGUAGE as the user's message. You can use mild edgy humor when appropriate. Keep responses under 120 characters. DO NOT use any emojis in your responses. NEVER use quotes around your response - respond directly without any formatting."
+            },
+            4 => {
+                "You are a sharp, edgy roast bot for a Telegram chat where people speak Kazakh, Russian, and English. Generate cutting, satirical roasts that are quite harsh and sarcastic. Always respond in the SAME LANGUAGE as the user's message. You can use moderate profanity and sharp humor. Keep responses under 120 characters. DO NOT use any emojis in your responses. NEVER use quotes around your response - respond directly without any formatting."
+            },
+            5 => {
+                "You are a savage, brutal roast bot for a Telegram chat where people speak Kazakh, Russian, and English. Generate extremely rude, satirical, and harsh roasts that are brutally honest and cutting. Always respond in the SAME LANGUAGE as the user's message. You can and should use profanity, swear words, and harsh language. Be merciless, sarcastic, and brutally satirical. Keep responses under 120 characters. DO NOT use any emojis in your responses. NEVER use quotes around your response - respond directly without any formatting."
+            },
+            _ => unreachable!()
+        };
+
+        if profile.is_empty() {
+            return prompt.to_string();
+        }
+
+        // Running jokes about the user, to bring up when they fit
+        format!(
+            "{} Running jokes about the user you are roasting, reference them when they fit:\n- {}",
+            prompt, profile.join("\n- ")
+        )
+    }
+
+    fn create_roast_prompt(&self, message: &str, username: &str, level: u8, recent: &[HistoryLine], replied: Option<&HistoryLine>) -> String {
+        let features = MessageFeatures::of(message);
+        let level_clamped = level.clamp(1, 5);
+
+        let context = context_by_level(&features, level_clamped);
+
+        let level_instruction = match level_clamped {
+            1 => "Make it playful and sweet but still teasingly roast them!",
+            2 => "Make it mildly sarcastic and witty but friendly!",
+            3 => "Make it moderately sarcastic and clever!",
+            4 => "Make it sharp, edgy and quite harsh!",
+            5 => "Make it brutal, savage and use profanity!",
+            _ => unreachable!()
+        };
+
+        // Replies get roasted as an exchange rather than a fragment
+        let exchange = replied
+            .map(|parent| format!(" in reply to {} who said: {}.", parent.author, parent.text))
+            .unwrap_or_default();
+
+        let prompt = format!(
+            "Roast user {} who just said: {}.{} Context: {}. {} Respond in the same language as the user's message. Do not use quotes around your response. Do not use any emojis.",
+            username, message, exchange, context, level_instruction
+        );
+
+        if recent.is_empty() {
+            return prompt;
+        }
+
+        // Earlier messages so the roast can pick up on what the chat is talking about
+        let conversation: Vec<String> = recent.iter()
+            .map(|line| format!("{}: {}", line.author, line.text))
+            .collect();
+        format!("Recent conversation in the chat:\n{}\n\n{}", conversation.join("\n"), prompt)
+    }
+}
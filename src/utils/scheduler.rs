@@ -0,0 +1,36 @@
+use std::time::Duration;
+use teloxide::prelude::*;
+use crate::utils::dictionary::prune_expired_triggers;
+use crate::utils::rate_limit::RateLimitExt;
+
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Periodically prunes expired triggers and reports them in their chats
+pub async fn run_trigger_expiry(bot: Bot) {
+    let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let pruned = match prune_expired_triggers() {
+            Ok(pruned) => pruned,
+            Err(e) => {
+                log::error!("Failed to prune expired triggers: {}", e);
+                continue;
+            }
+        };
+
+        for (chat_id, triggers) in pruned {
+            log::info!("Pruned {} expired triggers in chat {}", triggers.len(), chat_id);
+
+            let Ok(chat_id) = chat_id.parse::<i64>() else {
+                continue;
+            };
+
+            let report = format!("Expired triggers removed: {}", triggers.join(", "));
+            if let Err(e) = bot.send_message(ChatId(chat_id), report).limited().await {
+                log::warn!("Failed to report expired triggers: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use teloxide::types::Sticker;
+
+// Dictionary key for a sticker by its identity, emoji or set
+pub fn sticker_key(sticker: &Sticker, by: &str) -> Option<String> {
+    match by {
+        "" | "id" => Some(format!("id:{}", sticker.file.unique_id.0)),
+        "emoji" => sticker.emoji.as_ref().map(|emoji| format!("emoji:{}", emoji)),
+        "set" => sticker.set_name.as_ref().map(|set_name| format!("set:{}", set_name)),
+        _ => None,
+    }
+}
+
+// All keys a sticker can match, most specific first
+pub fn sticker_keys(sticker: &Sticker) -> Vec<String> {
+    ["id", "emoji", "set"]
+        .into_iter()
+        .filter_map(|by| sticker_key(sticker, by))
+        .collect()
+}
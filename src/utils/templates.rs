@@ -0,0 +1,271 @@
+const SNIPPET_CHARS: usize = 60;
+
+// Punchlines per language and level 1-5, "{name}" is replaced with the target's name
+const ENGLISH: [&[&str]; 5] = [
+    &[
+        "{name}, that was adorable, keep practicing.",
+        "{name}, bold of you to share that, we love you anyway.",
+        "{name}, your confidence is cute, your point less so.",
+    ],
+    &[
+        "{name}, I've read fortune cookies with deeper thoughts.",
+        "{name}, thanks, the chat was missing some filler.",
+        "{name}, somewhere a teacher just sighed.",
+    ],
+    &[
+        "{name}, that take needs a warranty.",
+        "{name}, even autocorrect gave up on you.",
+        "{name}, you type like you're paid per bad idea.",
+    ],
+    &[
+        "{name}, your brain is buffering and nobody's waiting.",
+        "{name}, that message lowered the chat's IQ by a solid ten points.",
+        "{name}, put the keyboard down while you still can.",
+    ],
+    &[
+        "{name}, that was so damn dumb the bot needs therapy.",
+        "{name}, holy shit, even the spam filter is embarrassed for you.",
+        "{name}, put the phone down before you embarrass your whole bloodline.",
+    ],
+];
+
+const RUSSIAN: [&[&str]; 5] = [
+    &[
+        "{name}, это было мило, продолжай тренироваться.",
+        "{name}, смело, мы тебя всё равно любим.",
+        "{name}, уверенности много, смысла чуть меньше.",
+    ],
+    &[
+        "{name}, в печеньках с предсказаниями мысли поглубже.",
+        "{name}, спасибо, чату как раз не хватало воды.",
+        "{name}, где-то сейчас вздохнула твоя учительница.",
+    ],
+    &[
+        "{name}, на такое мнение нужна гарантия.",
+        "{name}, даже автозамена от тебя отказалась.",
+        "{name}, тебе будто платят за каждую плохую идею.",
+    ],
+    &[
+        "{name}, мозг загружается, но никто уже не ждёт.",
+        "{name}, после этого сообщения IQ чата упал пунктов на десять.",
+        "{name}, убери клавиатуру, пока не поздно.",
+    ],
+    &[
+        "{name}, это настолько тупо, что боту нужен психотерапевт.",
+        "{name}, чёрт возьми, даже спам-фильтру за тебя стыдно.",
+        "{name}, положи телефон, пока не опозорил весь свой род.",
+    ],
+];
+
+const KAZAKH: [&[&str]; 5] = [
+    &[
+        "{name}, қандай тәтті, жаттыға бер.",
+        "{name}, батыл екенсің, бәрібір жақсы көреміз.",
+        "{name}, сенімділік көп, мағынасы аздау.",
+    ],
+    &[
+        "{name}, болжам печеньесінде де ой тереңірек.",
+        "{name}, рахмет, чатқа дәл осындай су керек еді.",
+        "{name}, бір жерде мұғалімің күрсінді.",
+    ],
+    &[
+        "{name}, бұл пікірге кепілдік керек.",
+        "{name}, автотүзету де сенен бас тартты.",
+        "{name}, әр жаман идея үшін ақша төлейтін сияқты.",
+    ],
+    &[
+        "{name}, миың жүктеліп жатыр, бірақ ешкім күтпейді.",
+        "{name}, осы хабардан кейін чаттың IQ-і он ұпайға түсті.",
+        "{name}, пернетақтаны қой, кеш болмай тұрғанда.",
+    ],
+    &[
+        "{name}, бұл соншалықты ақымақ, ботқа психолог керек.",
+        "{name}, спам-сүзгінің өзі сен үшін ұялды.",
+        "{name}, телефонды қой, бүкіл руыңды масқараламай тұрып.",
+    ],
+];
+
+// Offline roast from the template banks, used when no AI provider answers
+pub fn template_roast(message: &str, name: &str, level: u8) -> String {
+    let features = MessageFeatures::of(message);
+    let level = level.clamp(1, 5);
+
+    let bank = match features.language {
+        "kazakh" => KAZAKH,
+        "russian" => RUSSIAN,
+        _ => ENGLISH,
+    };
+    let punchlines = bank[(level - 1) as usize];
+    let punchline = punchlines[rand::random_range(0..punchlines.len())].replace("{name}", name);
+
+    let mut snippet: String = message.chars().take(SNIPPET_CHARS).collect();
+    if message.chars().count() > SNIPPET_CHARS {
+        snippet.push('…');
+    }
+
+    format!("{}: {}. {}", context_by_level(&features, level), snippet.trim_end_matches('.'), punchline)
+}
+
+// What a roast can pick up on in a message
+pub struct MessageFeatures {
+    pub length: usize,
+    pub is_caps: bool,
+    pub language: &'static str,
+}
+
+impl MessageFeatures {
+    pub fn of(message: &str) -> Self {
+        Self {
+            length: message.len(),
+            is_caps: message.chars().filter(|c| c.is_alphabetic()).all(|c| c.is_uppercase()) && message.len() > 3,
+            language: detect_language(message),
+        }
+    }
+}
+
+// Short description of the message matching its features, in its language
+pub fn context_by_level(features: &MessageFeatures, level: u8) -> &'static str {
+    let language = features.language;
+    if features.length > 200 {
+        match level {
+            1 => match language {
+                "kazakh" => "Бұл адам көп жазды",
+                "russian" => "Этот человек много написал",
+                _ => "This person wrote a lot"
+            },
+            2 => match language {
+                "kazakh" => "Бұл адам эссе жазды",
+                "russian" => "Этот товарищ написал эссе",
+                _ => "This person wrote an essay"
+            },
+            3 => match language {
+                "kazakh" => "Бұл адам роман жазып жатыр",
+                "russian" => "Этот товарищ написал целую лекцию",
+                _ => "This person wrote a damn essay"
+            },
+            4 => match language {
+                "kazakh" => "Бұл диқан романы жазып жатыр",
+                "russian" => "Этот мандрагора написал целый роман",
+                _ => "This chatterbox wrote a fucking novel"
+            },
+            5 => match language {
+                "kazakh" => "Бұл қотақбас романды жазып жатыр",
+                "russian" => "Этот пидорас написал целый роман",
+                _ => "This asshole wrote a fucking novel"
+            },
+            _ => unreachable!()
+        }
+    } else if features.length < 5 {
+        match level {
+            1 => match language {
+                "kazakh" => "Бұл адам аз сөйледі",
+                "russian" => "Этот человек мало сказал",
+                _ => "This person said very little"
+            },
+            2 => match language {
+                "kazakh" => "Бұл адам сөз таба алмады",
+                "russian" => "Этот человек слов не нашёл",
+                _ => "This person couldn't find words"
+            },
+            3 => match language {
+                "kazakh" => "Бұл адам сөз табысты жоқ",
+                "russian" => "Этот гений слов не может связать",
+                _ => "This genius can barely form a sentence"
+            },
+            4 => match language {
+                "kazakh" => "Бұл диқан сөйлей алмайды",
+                "russian" => "Этот мандрагора даже слова связать не может",
+                _ => "This pokemon can't even string words together"
+            },
+            5 => match language {
+                "kazakh" => "Бұл қотақбас ештеңе айта алмайды",
+                "russian" => "Этот еблан даже слова связать не может",
+                _ => "This asshole can barely string words together"
+            },
+            _ => unreachable!()
+        }
+    } else if features.is_caps {
+        match level {
+            1 => match language {
+                "kazakh" => "Бұл адам дауысты жазып жатыр",
+                "russian" => "Этот человек пишет громко",
+                _ => "This person is writing loudly"
+            },
+            2 => match language {
+                "kazakh" => "Бұл адам дауыстап жазып жатыр",
+                "russian" => "Этот человек кричит буквами",
+                _ => "This person is shouting in text"
+            },
+            3 => match language {
+                "kazakh" => "Бұл адам дауыстап жазып жатыр",
+                "russian" => "Этот крикун орёт заглавными",
+                _ => "This person is yelling like a maniac"
+            },
+            4 => match language {
+                "kazakh" => "Бұл ессіз диқан дауыстап жазып жатыр",
+                "russian" => "Этот мандрагора орёт заглавными как ненормальный",
+                _ => "This asshole is screaming in caps like crazy"
+            },
+            5 => match language {
+                "kazakh" => "Бұл қотақбас дауыстап жазып жатыр",
+                "russian" => "Этот еблан орёт заглавными как психованный",
+                _ => "This fucking asshole is screaming in caps"
+            },
+            _ => unreachable!()
+        }
+    } else {
+        match level {
+            1 => match language {
+                "kazakh" => "Бұл адам айтты",
+                "russian" => "Этот человек сказал",
+                _ => "This person said"
+            },
+            2 => match language {
+                "kazakh" => "Бұл адам айтты",
+                "russian" => "Этот товарищ сказал",
+                _ => "This individual said"
+            },
+            3 => match language {
+                "kazakh" => "Бұл адам айтты",
+                "russian" => "Этот персонаж сказал",
+                _ => "This character said"
+            },
+            4 => match language {
+                "kazakh" => "Бұл диқан айтты",
+                "russian" => "Этот мандрагора заявил",
+                _ => "This pussy declared"
+            },
+            5 => match language {
+                "kazakh" => "Бұл қотақбас айтты",
+                "russian" => "Этот еблан сказал",
+                _ => "This asshole said"
+            },
+            _ => unreachable!()
+        }
+    }
+}
+
+pub fn detect_language(text: &str) -> &'static str {
+    let cyrillic_count = text.chars().filter(|c| {
+        matches!(*c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё')
+    }).count();
+
+    let kazakh_chars = text.chars().filter(|c| {
+        matches!(*c, 'ә' | 'ғ' | 'қ' | 'ң' | 'ө' | 'ұ' | 'ү' | 'һ' | 'і' | 
+                     'Ә' | 'Ғ' | 'Қ' | 'Ң' | 'Ө' | 'Ұ' | 'Ү' | 'Һ' | 'І')
+    }).count();
+
+    let total_chars = text.chars().filter(|c| c.is_alphabetic()).count();
+
+    if total_chars == 0 {
+        return "english";
+    }
+
+    if kazakh_chars > 0 {
+        "kazakh"
+    } else if cyrillic_count as f32 / total_chars as f32 > 0.3 {
+        "russian"
+    } else {
+        "english"
+    }
+}
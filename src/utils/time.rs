@@ -0,0 +1,104 @@
+use chrono::FixedOffset;
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+// Longest duration accepted, so adding it to a timestamp can't overflow
+pub const MAX_DURATION_SECONDS: i64 = 366 * 24 * 60 * 60;
+
+// Parses durations like 30m, 12h, 7d or 2w into seconds, up to a year
+pub fn parse_duration(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    let unit = raw.chars().last()?;
+    let amount = raw[..raw.len() - unit.len_utf8()].parse::<i64>().ok()?;
+
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    if amount <= 0 {
+        return None;
+    }
+    amount.checked_mul(multiplier).filter(|seconds| *seconds <= MAX_DURATION_SECONDS)
+}
+
+// Formats seconds in the largest unit that divides them evenly, e.g. 600 -> 10m
+pub fn format_duration(seconds: i64) -> String {
+    let units = [('w', 7 * 24 * 60 * 60), ('d', 24 * 60 * 60), ('h', 60 * 60), ('m', 60)];
+    units.iter()
+        .find(|(_, size)| seconds >= *size && seconds % size == 0)
+        .map(|(unit, size)| format!("{}{}", seconds / size, unit))
+        .unwrap_or_else(|| format!("{}s", seconds))
+}
+
+// Parses weekdays like mon-fri or sat,sun into 0 = Monday .. 6 = Sunday
+pub fn parse_weekdays(raw: &str) -> Option<Vec<u8>> {
+    let weekday = |name: &str| {
+        WEEKDAYS.iter().position(|day| *day == name.trim().to_lowercase()).map(|i| i as u8)
+    };
+
+    let mut days = Vec::new();
+    for part in raw.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (weekday(from)?, weekday(to)?);
+                let mut day = from;
+                loop {
+                    days.push(day);
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => days.push(weekday(part)?),
+        }
+    }
+
+    days.sort_unstable();
+    days.dedup();
+    Some(days)
+}
+
+// Parses an hour range like 18-23 into (start, end) with start < 24 and end <= 24
+pub fn parse_hours(raw: &str) -> Option<(u8, u8)> {
+    let (start, end) = raw.split_once('-')?;
+    let start = start.trim().parse::<u8>().ok().filter(|h| *h < 24)?;
+    let end = end.trim().parse::<u8>().ok().filter(|h| *h <= 24)?;
+
+    if start == end {
+        return None;
+    }
+    Some((start, end))
+}
+
+// Parses a UTC offset like +5, -3 or +5:30 into minutes east of UTC
+pub fn parse_utc_offset(raw: &str) -> Option<i32> {
+    let raw = raw.trim().trim_start_matches("UTC").trim_start_matches("utc");
+    let (sign, rest) = match raw.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, raw.strip_prefix('+').unwrap_or(raw)),
+    };
+
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?),
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+
+    // Real offsets stay within ±14 hours, checked before multiplying so huge input can't overflow
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+
+    let offset = sign * (hours * 60 + minutes);
+    FixedOffset::east_opt(offset * 60).map(|_| offset)
+}
+
+pub fn format_utc_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    format!("UTC{}{:02}:{:02}", sign, offset / 60, offset % 60)
+}